@@ -1,6 +1,8 @@
 use serde::Deserialize;
 use std::path::PathBuf;
 
+use crate::error::RenderError;
+
 /// Render configuration loaded from a JSON file or CLI arguments
 #[derive(Debug, Deserialize)]
 pub struct RenderConfig {
@@ -14,6 +16,12 @@ pub struct RenderConfig {
     pub fade_in: f32,
     #[serde(default)]
     pub fade_out: f32,
+    /// Fade timing: `linear` (default), `ease-in`, `ease-out`, `ease-in-out` or `cubic`
+    #[serde(default)]
+    pub fade_curve: Option<String>,
+    /// Hex color opaque formats fade from and to (default black); alpha formats fade transparency
+    #[serde(default)]
+    pub fade_color: Option<String>,
     #[serde(default)]
     pub bitrate: Option<String>,
     #[serde(default)]
@@ -21,15 +29,132 @@ pub struct RenderConfig {
     #[serde(default)]
     pub open: bool,
     #[serde(default)]
-    pub preview: Option<usize>, // None = no preview, Some(n) = preview n frames
+    pub preview: Option<usize>, // None = no preview, Some(n) = preview frame n (from 0)
     #[serde(default)]
     pub file_pattern: Option<String>,
+    /// Only use frames inside this folder of a ZIP or tar input, e.g. `shotA`
+    #[serde(default)]
+    pub zip_subfolder: Option<String>,
+    /// Image types accepted as frames: `png` (default), `jpg`, `webp`, `tiff`, `exr`, `tga`
+    #[serde(default)]
+    pub input_extensions: Option<Vec<String>>,
+    /// What to do about gaps in frame numbers: `skip` (default), `hold` or `fail`
+    #[serde(default)]
+    pub on_frame_gaps: Option<String>,
+    /// First frame number to render (inclusive)
+    #[serde(default)]
+    pub start_frame: Option<u64>,
+    /// Last frame number to render (inclusive)
+    #[serde(default)]
+    pub end_frame: Option<u64>,
+    /// Render every Nth frame, counting from `start_frame`
+    #[serde(default)]
+    pub frame_step: Option<u32>,
+    /// ProRes profile for `mov` output: proxy, lt, standard, hq, 4444 or 4444xq
+    #[serde(default)]
+    pub prores_profile: Option<String>,
+    /// Encode `webp` output losslessly
+    #[serde(default)]
+    pub lossless: bool,
+    /// Lossy quality from 0 to 100 for `webp` output
+    #[serde(default)]
+    pub quality: Option<u32>,
+    /// Number of times animated output plays; 0 or unset loops forever
+    #[serde(default)]
+    pub loop_count: Option<u32>,
+    /// Palette size for `gif` (default 256) and optional palette reduction for `apng` (2-256)
+    #[serde(default)]
+    pub max_colors: Option<u32>,
+    /// Output width in pixels; `gif` defaults to 640 wide when neither size is set
+    #[serde(default)]
+    pub width: Option<u32>,
+    /// Output height in pixels
+    #[serde(default)]
+    pub height: Option<u32>,
+    /// How frames fill `width` x `height`: `contain`, `cover` or `stretch` (default)
+    #[serde(default)]
+    pub fit: Option<String>,
+    /// Rectangle cut from the source frames before scaling: `W:H` (centered) or `W:H:X:Y`
+    #[serde(default)]
+    pub crop: Option<String>,
+    /// Transparent margins added after scaling: `ALL`, `V:H` or `TOP:RIGHT:BOTTOM:LEFT`
+    #[serde(default)]
+    pub pad: Option<String>,
+    /// What formats without alpha (mp4, non-4444 mov) and paletted ones (gif, apng with
+    /// `max_colors`) flatten transparent frames onto:
+    /// a hex color such as `#ffffff`, `checkerboard`, or an image/video path
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Scaling algorithm passed to ffmpeg's `scale` filter (default `lanczos`)
+    #[serde(default)]
+    pub scale_flags: Option<String>,
+    /// GIF dithering mode, e.g. `bayer`, `sierra2_4a` (default) or `none`
+    #[serde(default)]
+    pub dither: Option<String>,
+    /// GIF palette statistics mode: `full` (default) or `diff`
+    #[serde(default)]
+    pub stats_mode: Option<String>,
+    /// Keep a transparent entry in the GIF palette (default true)
+    #[serde(default)]
+    pub reserve_transparent: Option<bool>,
+    /// Read every frame's header before encoding to catch unreadable or mismatched files (default true)
+    #[serde(default)]
+    pub validate_frames: Option<bool>,
+    /// Fully decode every frame during validation, catching truncated image data and
+    /// confirming alpha is used (slower)
+    #[serde(default)]
+    pub decode_frames: bool,
     #[serde(default)]
     pub verbose: bool,
     #[serde(default)]
     pub verbose_ffmpeg: bool,
 }
 
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            input: PathBuf::new(),
+            output: String::new(),
+            fps: default_fps(),
+            format: default_format(),
+            fade_in: 0.0,
+            fade_out: 0.0,
+            fade_curve: None,
+            fade_color: None,
+            bitrate: None,
+            crf: None,
+            open: false,
+            preview: None,
+            file_pattern: None,
+            zip_subfolder: None,
+            input_extensions: None,
+            on_frame_gaps: None,
+            start_frame: None,
+            end_frame: None,
+            frame_step: None,
+            prores_profile: None,
+            lossless: false,
+            quality: None,
+            loop_count: None,
+            max_colors: None,
+            width: None,
+            height: None,
+            fit: None,
+            crop: None,
+            pad: None,
+            background: None,
+            scale_flags: None,
+            dither: None,
+            stats_mode: None,
+            reserve_transparent: None,
+            validate_frames: None,
+            decode_frames: false,
+            verbose: false,
+            verbose_ffmpeg: false,
+        }
+    }
+}
+
 fn default_fps() -> u32 {
     30
 }
@@ -40,10 +165,13 @@ fn default_format() -> String {
 
 impl RenderConfig {
     /// Load configuration from a JSON file
-    pub fn from_file(path: &str) -> Result<Self, String> {
-        let config_str = std::fs::read_to_string(path)
-            .map_err(|_| format!("❌ Config file '{}' not found.", path))?;
-        serde_json::from_str(&config_str).map_err(|e| format!("❌ Failed to parse config: {}", e))
+    pub fn from_file(path: &str) -> Result<Self, RenderError> {
+        let config_str =
+            std::fs::read_to_string(path).map_err(|source| RenderError::ConfigRead {
+                path: PathBuf::from(path),
+                source,
+            })?;
+        Ok(serde_json::from_str(&config_str)?)
     }
 
     pub fn is_preview(&self) -> bool {
@@ -0,0 +1,207 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Errors returned by the rendering pipeline
+#[derive(Debug)]
+pub enum RenderError {
+    /// The configured output path is empty
+    EmptyOutput,
+    /// The requested output format is not supported
+    UnsupportedFormat(String),
+    /// A configuration option has a value that is not recognised
+    InvalidOption { name: &'static str, value: String },
+    /// The input folder or archive does not exist
+    InputNotFound(PathBuf),
+    /// The input file is neither a frame archive nor a video ffprobe can read
+    UnrecognizedInput(PathBuf),
+    /// ffmpeg could not be found in `PATH`
+    FfmpegNotFound,
+    /// ffprobe, needed to inspect video input, could not be found in `PATH`
+    FfprobeNotFound,
+    /// ffmpeg ran but exited unsuccessfully
+    FfmpegFailed {
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    /// No input frames matched the file pattern
+    NoFramesMatched { dir: PathBuf, pattern: String },
+    /// An in-memory render was given no frames
+    NoMemoryFrames,
+    /// Two frames in an archive share a file name in different folders
+    DuplicateFrameName {
+        name: String,
+        first: String,
+        second: String,
+    },
+    /// Frame numbers have gaps or duplicates and `on_frame_gaps` is `fail`
+    FrameSequenceGaps(Vec<String>),
+    /// Input frames use more than one image format
+    MixedFrameFormats { first: String, second: String },
+    /// Input frames could not be read or decoded; each entry names the file
+    CorruptFrames(Vec<String>),
+    /// A frame's size or color type differs from the first frame
+    FrameMismatch {
+        first: String,
+        expected: String,
+        other: String,
+        found: String,
+    },
+    /// A preview frame index is outside the available frames
+    FrameIndexOutOfRange { index: usize, count: usize },
+    /// The config file could not be read
+    ConfigRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The config file is not valid JSON or is missing fields
+    ConfigParse(serde_json::Error),
+    /// The ZIP archive could not be opened or read
+    ZipRead(zip::result::ZipError),
+    /// The file pattern is not a valid glob
+    InvalidPattern(glob::PatternError),
+    /// The render was cancelled through a [`CancellationToken`](crate::CancellationToken)
+    Cancelled,
+    /// Any other I/O failure, with a short description of what was attempted
+    Io {
+        context: String,
+        source: std::io::Error,
+    },
+}
+
+impl RenderError {
+    pub(crate) fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        RenderError::Io {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::EmptyOutput => write!(f, "Output path cannot be empty."),
+            RenderError::UnsupportedFormat(format) => {
+                write!(f, "Unsupported format: {}", format)
+            }
+            RenderError::InvalidOption { name, value } => {
+                write!(f, "Invalid value '{}' for {}", value, name)
+            }
+            RenderError::InputNotFound(path) => {
+                write!(f, "Input path '{}' does not exist.", path.display())
+            }
+            RenderError::UnrecognizedInput(path) => write!(
+                f,
+                "Input '{}' is not a frame folder, a ZIP/tar archive or a video file.",
+                path.display()
+            ),
+            RenderError::FfmpegNotFound => write!(
+                f,
+                "ffmpeg not found. Please install ffmpeg and ensure it is in your PATH."
+            ),
+            RenderError::FfprobeNotFound => write!(
+                f,
+                "ffprobe not found. It ships with ffmpeg; ensure it is in your PATH."
+            ),
+            RenderError::FfmpegFailed { exit_code, stderr } => {
+                write!(f, "ffmpeg exited with code {}", exit_code.unwrap_or(-1))?;
+                let stderr = stderr.trim();
+                if !stderr.is_empty() {
+                    write!(f, ": {}", stderr)?;
+                }
+                Ok(())
+            }
+            RenderError::NoFramesMatched { dir, pattern } => write!(
+                f,
+                "No input files found in '{}' matching pattern '{}'.",
+                dir.display(),
+                pattern
+            ),
+            RenderError::NoMemoryFrames => write!(f, "No frames were provided to render."),
+            RenderError::DuplicateFrameName {
+                name,
+                first,
+                second,
+            } => write!(
+                f,
+                "Frame name '{}' appears more than once in the archive ('{}' and '{}'). \
+                 Set zip_subfolder to pick one folder.",
+                name, first, second
+            ),
+            RenderError::FrameSequenceGaps(issues) => {
+                write!(f, "Frame sequence is incomplete: {}", issues.join(", "))
+            }
+            RenderError::MixedFrameFormats { first, second } => write!(
+                f,
+                "Input frames mix image formats ('{}' and '{}'). \
+                 Narrow input_extensions or file_pattern to a single format.",
+                first, second
+            ),
+            RenderError::CorruptFrames(frames) => {
+                write!(
+                    f,
+                    "Input frames are corrupt or truncated: {}",
+                    frames.join("; ")
+                )
+            }
+            RenderError::FrameMismatch {
+                first,
+                expected,
+                other,
+                found,
+            } => write!(
+                f,
+                "Frame '{}' is {} but '{}' is {}. All frames must share size and color type.",
+                other, found, first, expected
+            ),
+            RenderError::FrameIndexOutOfRange { index, count } => write!(
+                f,
+                "Frame index {} out of range (0..{})",
+                index,
+                count.saturating_sub(1)
+            ),
+            RenderError::ConfigRead { path, source } => write!(
+                f,
+                "Config file '{}' could not be read: {}",
+                path.display(),
+                source
+            ),
+            RenderError::ConfigParse(e) => write!(f, "Failed to parse config: {}", e),
+            RenderError::ZipRead(e) => write!(f, "Failed to read zip archive: {}", e),
+            RenderError::InvalidPattern(e) => write!(f, "Invalid file pattern: {}", e),
+            RenderError::Cancelled => write!(f, "Render cancelled."),
+            RenderError::Io { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RenderError::ConfigRead { source, .. } => Some(source),
+            RenderError::ConfigParse(e) => Some(e),
+            RenderError::ZipRead(e) => Some(e),
+            RenderError::InvalidPattern(e) => Some(e),
+            RenderError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for RenderError {
+    fn from(e: serde_json::Error) -> Self {
+        RenderError::ConfigParse(e)
+    }
+}
+
+impl From<zip::result::ZipError> for RenderError {
+    fn from(e: zip::result::ZipError) -> Self {
+        RenderError::ZipRead(e)
+    }
+}
+
+impl From<glob::PatternError> for RenderError {
+    fn from(e: glob::PatternError) -> Self {
+        RenderError::InvalidPattern(e)
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+use crate::cancel::CancellationToken;
+use crate::error::RenderError;
+use crate::ffmpeg::filter::{Filter, FilterGraph};
+use crate::ffmpeg::source::FrameSource;
+use crate::observer::{RenderObserver, RenderStage};
+use crate::report::RenderReport;
+use crate::utils;
+
+/// Pixel format encoded for APNG: a shared palette when `max_colors` is set,
+/// otherwise full RGBA
+pub fn pixel_format(max_colors: Option<u32>) -> &'static str {
+    match max_colors {
+        Some(_) => "pal8",
+        None => "rgba",
+    }
+}
+
+/// Render an animated PNG with full 8-bit alpha.
+///
+/// When `max_colors` is set, frames are reduced to a shared palette (with a
+/// transparent entry) in a single `palettegen`/`paletteuse` filter graph.
+#[allow(clippy::too_many_arguments)]
+pub fn render_apng(
+    source: &FrameSource,
+    output: &str,
+    fps: u32,
+    loop_count: Option<u32>,
+    max_colors: Option<u32>,
+    filters: &FilterGraph,
+    verbose_ffmpeg: bool,
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<RenderReport, RenderError> {
+    if let Some(colors) = max_colors {
+        if !(2..=256).contains(&colors) {
+            return Err(RenderError::InvalidOption {
+                name: "max_colors",
+                value: colors.to_string(),
+            });
+        }
+    }
+
+    let mut args = source.input_args(fps);
+
+    let mut graph = filters.clone();
+    if let Some(colors) = max_colors {
+        let streams = graph.push(Filter::split(2)).outputs(2);
+        graph.open(&[&streams[0]]).push(
+            Filter::palettegen()
+                .opt("max_colors", colors)
+                .opt("reserve_transparent", 1),
+        );
+        let palette = graph.outputs(1);
+        graph
+            .open(&[&streams[1], &palette[0]])
+            .push(Filter::paletteuse());
+    }
+    if !graph.is_empty() {
+        args.push("-filter_complex".into());
+        args.push(graph.to_string());
+    }
+
+    args.extend_from_slice(&[
+        "-c:v".into(),
+        "apng".into(),
+        "-pix_fmt".into(),
+        pixel_format(max_colors).into(),
+        "-plays".into(),
+        loop_count.unwrap_or(0).to_string(),
+        "-f".into(),
+        "apng".into(),
+    ]);
+
+    args.push("-y".into());
+    args.push(output.into());
+    if !verbose_ffmpeg {
+        args.push("-loglevel".into());
+        args.push("warning".into());
+    }
+
+    observer.on_stage(RenderStage::Encoding);
+    let (_, apng_stderr) = utils::run_ffmpeg_with_input(
+        &args,
+        source.stdin_feed(),
+        &mut |p| observer.on_frame_progress(p),
+        cancel,
+    )
+    .inspect_err(|e| utils::remove_partial_output(e, output))?;
+    let apng_warnings = utils::scan_ffmpeg_stderr(&apng_stderr);
+
+    let notes = match max_colors {
+        Some(colors) => format!("APNG export ({} color palette)", colors),
+        None => "APNG export (RGBA)".to_string(),
+    };
+    Ok(RenderReport {
+        output_path: PathBuf::from(output),
+        frames_rendered: None,
+        ffmpeg_warnings: apng_warnings,
+        preview: false,
+        notes: Some(notes),
+        sequence_issues: Vec::new(),
+        frame_check: None,
+    })
+}
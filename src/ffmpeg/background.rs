@@ -0,0 +1,228 @@
+use std::path::PathBuf;
+
+use crate::error::RenderError;
+use crate::ffmpeg::filter::{Filter, FilterGraph};
+
+/// Size of one checkerboard square in pixels
+const CHECKER_SIZE: u32 = 16;
+
+/// What transparent frames are flattened onto for formats without alpha
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Background {
+    /// A solid `0xRRGGBB` color
+    Color(String),
+    /// Light grey and white squares, like an image editor's transparency grid
+    Checkerboard,
+    /// An image or video file, stretched to the frame size and looped
+    Media(PathBuf),
+}
+
+impl Background {
+    /// Parse a hex color (`#ffffff`, `fff`, `0xffffff`), `checkerboard`, or a
+    /// path to an existing image or video. An existing file wins over a bare
+    /// color without `#`/`0x`, so a background named e.g. `cafe` is not
+    /// mistaken for one.
+    pub fn parse(value: &str) -> Result<Self, RenderError> {
+        if value.eq_ignore_ascii_case("checkerboard") {
+            return Ok(Background::Checkerboard);
+        }
+        let prefixed = value.starts_with('#') || value.starts_with("0x");
+        let path = PathBuf::from(value);
+        if !prefixed && path.is_file() {
+            return Ok(Background::Media(path));
+        }
+        parse_hex_color(value)
+            .map(Background::Color)
+            .ok_or_else(|| RenderError::InvalidOption {
+                name: "background",
+                value: value.to_string(),
+            })
+    }
+
+    /// Composite the graph's current stream over the background. With
+    /// `high_bit_depth`, the composite is done in 10-bit YUV so 16-bit frames
+    /// keep their precision.
+    pub fn composite(&self, graph: &mut FilterGraph, fps: u32, high_bit_depth: bool) {
+        let (base, frames) = match self {
+            Background::Color(color) => {
+                let streams = graph.push(Filter::split(2)).outputs(2);
+                graph
+                    .open(&[&streams[1]])
+                    .push(Filter::format("yuv444p"))
+                    .push(Filter::drawbox(color).opt("t", "fill"));
+                if high_bit_depth {
+                    graph.push(Filter::format("yuv444p10le"));
+                }
+                (graph.outputs(1).remove(0), streams[0].clone())
+            }
+            Background::Checkerboard => {
+                let streams = graph.push(Filter::split(2)).outputs(2);
+                let squares = format!(
+                    "if(mod(floor(X/{size})+floor(Y/{size}),2),204,255)",
+                    size = CHECKER_SIZE
+                );
+                graph
+                    .open(&[&streams[1]])
+                    .push(Filter::format("gray"))
+                    .push(Filter::new("geq").opt("lum", squares));
+                if high_bit_depth {
+                    graph.push(Filter::format("yuv444p10le"));
+                }
+                (graph.outputs(1).remove(0), streams[0].clone())
+            }
+            Background::Media(path) => {
+                let frames = graph.outputs(1).remove(0);
+                // Looped and retimed so the background has a frame for every frame
+                graph
+                    .open(&[])
+                    .push(Filter::movie(&path.to_string_lossy()).opt("loop", 0))
+                    .push(Filter::setpts(&format!("N/({}*TB)", fps)));
+                let media = graph.outputs(1).remove(0);
+                // Stretch the background to the frame size
+                let mut scaled = graph
+                    .open(&[&media, &frames])
+                    .push(Filter::new("scale2ref"))
+                    .outputs(2);
+                (scaled.remove(0), scaled.remove(0))
+            }
+        };
+        let format = if high_bit_depth { "yuv444p10" } else { "auto" };
+        graph
+            .open(&[&base, &frames])
+            .push(Filter::overlay().opt("shortest", 1).opt("format", format));
+    }
+}
+
+impl std::fmt::Display for Background {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Background::Color(color) => write!(f, "{}", color.replace("0x", "#")),
+            Background::Checkerboard => f.write_str("checkerboard"),
+            Background::Media(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+pub(crate) fn parse_hex_color(value: &str) -> Option<String> {
+    let hex = value
+        .strip_prefix('#')
+        .or_else(|| value.strip_prefix("0x"))
+        .unwrap_or(value);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    match hex.len() {
+        6 => Some(format!("0x{}", hex.to_lowercase())),
+        3 => Some(format!(
+            "0x{}",
+            hex.chars()
+                .flat_map(|c| [c, c])
+                .collect::<String>()
+                .to_lowercase()
+        )),
+        _ => None,
+    }
+}
+
+/// Whether `pix_fmt` keeps an alpha channel
+pub fn has_alpha(pix_fmt: &str) -> bool {
+    [
+        "yuva", "rgba", "bgra", "argb", "abgr", "gbrap", "ya8", "ya16", "pal8",
+    ]
+    .iter()
+    .any(|layout| pix_fmt.starts_with(layout))
+}
+
+/// Whether `pix_fmt` is palette based, keeping on/off transparency at best
+pub fn is_paletted(pix_fmt: &str) -> bool {
+    pix_fmt == "pal8"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_colors_checkerboard_and_paths() {
+        assert_eq!(
+            Background::parse("#FFFFFF").unwrap(),
+            Background::Color("0xffffff".into())
+        );
+        assert_eq!(
+            Background::parse("f0a").unwrap(),
+            Background::Color("0xff00aa".into())
+        );
+        assert_eq!(
+            Background::parse("0x112233").unwrap(),
+            Background::Color("0x112233".into())
+        );
+        assert_eq!(
+            Background::parse("Checkerboard").unwrap(),
+            Background::Checkerboard
+        );
+        assert_eq!(
+            Background::parse("tests/testdata/frame_0016.png").unwrap(),
+            Background::Media("tests/testdata/frame_0016.png".into())
+        );
+        assert!(Background::parse("#12345").is_err());
+        assert!(Background::parse("missing/background.png").is_err());
+    }
+
+    #[test]
+    fn existing_files_win_over_bare_hex_colors() {
+        // Only bare names can be hex colors, so the file sits in the crate root
+        std::fs::write("decade", b"").unwrap();
+        let parsed = (Background::parse("decade"), Background::parse("#decade"));
+        std::fs::remove_file("decade").unwrap();
+
+        assert_eq!(parsed.0.unwrap(), Background::Media("decade".into()));
+        assert_eq!(parsed.1.unwrap(), Background::Color("0xdecade".into()));
+        assert_eq!(
+            Background::parse("bad").unwrap(),
+            Background::Color("0xbbaadd".into())
+        );
+    }
+
+    #[test]
+    fn composites_over_a_solid_color() {
+        let background = Background::parse("#ffffff").unwrap();
+        let mut graph = FilterGraph::new().then(Filter::scale(320, -2));
+        background.composite(&mut graph, 30, false);
+        assert_eq!(
+            graph.to_string(),
+            "[0:v]scale=320:-2,split=2[v1][v2];\
+             [v2]format=yuv444p,drawbox=color=0xffffff:t=fill[v3];\
+             [v3][v1]overlay=shortest=1:format=auto"
+        );
+        assert_eq!(background.to_string(), "#ffffff");
+    }
+
+    #[test]
+    fn high_bit_depth_composites_in_10_bit() {
+        let mut graph = FilterGraph::new();
+        Background::Checkerboard.composite(&mut graph, 30, true);
+        let graph = graph.to_string();
+        assert!(graph.contains(",format=yuv444p10le[v3];"));
+        assert!(graph.ends_with("overlay=shortest=1:format=yuv444p10"));
+    }
+
+    #[test]
+    fn media_backgrounds_loop_and_scale_to_the_frames() {
+        let mut graph = FilterGraph::new();
+        Background::Media("bg.png".into()).composite(&mut graph, 24, false);
+        assert_eq!(
+            graph.to_string(),
+            "[0:v]null[v1];movie=bg.png:loop=0,setpts=N/(24*TB)[v2];\
+             [v2][v1]scale2ref[v3][v4];[v3][v4]overlay=shortest=1:format=auto"
+        );
+    }
+
+    #[test]
+    fn detects_alpha_pixel_formats() {
+        assert!(has_alpha("yuva420p"));
+        assert!(has_alpha("yuva444p10le"));
+        assert!(has_alpha("bgra"));
+        assert!(!has_alpha("yuv420p"));
+        assert!(!has_alpha("yuv422p10le"));
+    }
+}
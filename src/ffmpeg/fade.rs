@@ -0,0 +1,243 @@
+use crate::error::RenderError;
+use crate::ffmpeg::background::parse_hex_color;
+use crate::ffmpeg::filter::{FadeDirection, Filter};
+
+/// Timing curve of a fade
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FadeCurve {
+    #[default]
+    Linear,
+    /// Starts slowly and speeds up (quadratic)
+    EaseIn,
+    /// Starts quickly and slows down (quadratic)
+    EaseOut,
+    /// Slow at both ends (smoothstep)
+    EaseInOut,
+    /// Slow at both ends with a steeper middle (cubic)
+    Cubic,
+}
+
+impl FadeCurve {
+    pub fn from_option(value: Option<&str>) -> Result<Self, RenderError> {
+        match value.map(|v| v.to_lowercase()).as_deref() {
+            None | Some("linear") => Ok(FadeCurve::Linear),
+            Some("ease-in") => Ok(FadeCurve::EaseIn),
+            Some("ease-out") => Ok(FadeCurve::EaseOut),
+            Some("ease-in-out") => Ok(FadeCurve::EaseInOut),
+            Some("cubic") => Ok(FadeCurve::Cubic),
+            Some(_) => Err(RenderError::InvalidOption {
+                name: "fade_curve",
+                value: value.unwrap_or_default().to_string(),
+            }),
+        }
+    }
+
+    /// ffmpeg expression mapping the progress stored in variable 0 (0 to 1)
+    /// to the eased progress
+    fn expr(self) -> &'static str {
+        match self {
+            FadeCurve::Linear => "ld(0)",
+            FadeCurve::EaseIn => "ld(0)*ld(0)",
+            FadeCurve::EaseOut => "1-(1-ld(0))*(1-ld(0))",
+            FadeCurve::EaseInOut => "ld(0)*ld(0)*(3-2*ld(0))",
+            FadeCurve::Cubic => "if(lt(ld(0),0.5),4*pow(ld(0),3),1-4*pow(1-ld(0),3))",
+        }
+    }
+}
+
+/// What a fade blends the frames towards
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FadeTarget {
+    /// Transparency, for formats that keep full alpha
+    Alpha,
+    /// A solid `0xRRGGBB` color, for opaque and paletted formats
+    Color(String),
+}
+
+impl FadeTarget {
+    /// Alpha for formats that keep it, otherwise `color` (default black)
+    pub fn for_output(keeps_alpha: bool, color: Option<&str>) -> Result<Self, RenderError> {
+        let color = color
+            .map(|value| {
+                parse_hex_color(value).ok_or_else(|| RenderError::InvalidOption {
+                    name: "fade_color",
+                    value: value.to_string(),
+                })
+            })
+            .transpose()?;
+        Ok(if keeps_alpha {
+            FadeTarget::Alpha
+        } else {
+            FadeTarget::Color(color.unwrap_or_else(|| "0x000000".into()))
+        })
+    }
+}
+
+/// A fade in or out over `duration` seconds from `start`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fade {
+    pub direction: FadeDirection,
+    pub start: f32,
+    pub duration: f32,
+    pub curve: FadeCurve,
+    pub target: FadeTarget,
+    /// Evaluate eased fades in 16-bit RGB, for outputs deeper than 8 bits
+    pub high_bit_depth: bool,
+}
+
+impl Fade {
+    /// Linear fades use ffmpeg's `fade` filter; eased ones evaluate the curve
+    /// per pixel with `geq`, only while the fade runs
+    pub fn filters(&self) -> Vec<Filter> {
+        if self.curve == FadeCurve::Linear {
+            let fade = Filter::fade(self.direction, self.start, self.duration);
+            return vec![match &self.target {
+                FadeTarget::Alpha => fade.opt("alpha", 1),
+                FadeTarget::Color(color) => fade.opt("color", color),
+            }];
+        }
+
+        let end = self.start + self.duration;
+        let progress = format!("st(0,clip((T-{})/{},0,1))", self.start, self.duration);
+        // How much of the frame shows, from 0 (faded) to 1
+        let (visible, enable) = match self.direction {
+            FadeDirection::In => (
+                format!("{};{}", progress, self.curve.expr()),
+                format!("lte(t,{})", end),
+            ),
+            FadeDirection::Out => (
+                format!("{};1-({})", progress, self.curve.expr()),
+                format!("gte(t,{})", self.start),
+            ),
+        };
+        // geq works in the value range of the pixel format it is given
+        let (depth, max) = if self.high_bit_depth {
+            ("16le", 0xffff)
+        } else {
+            ("", 0xff)
+        };
+        let (format, geq) = match &self.target {
+            FadeTarget::Alpha => (
+                format!("gbrap{}", depth),
+                Filter::new("geq")
+                    .opt("r", "r(X,Y)")
+                    .opt("g", "g(X,Y)")
+                    .opt("b", "b(X,Y)")
+                    .opt("a", format!("alpha(X,Y)*({})", visible)),
+            ),
+            FadeTarget::Color(color) => {
+                let rgb = u32::from_str_radix(color.trim_start_matches("0x"), 16).unwrap_or(0);
+                let channel = |plane: &str, shift: u32| {
+                    let target = ((rgb >> shift) & 0xff) * max / 0xff;
+                    format!("{}(X,Y)*({v})+{}*(1-({v}))", plane, target, v = visible)
+                };
+                (
+                    format!("gbrp{}", depth),
+                    Filter::new("geq")
+                        .opt("r", channel("r", 16))
+                        .opt("g", channel("g", 8))
+                        .opt("b", channel("b", 0)),
+                )
+            }
+        };
+        // Planar RGB keeps geq's value range fixed at 0-255 (or 0-65535)
+        vec![Filter::format(&format), geq.opt("enable", enable)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fade(direction: FadeDirection, curve: FadeCurve, target: FadeTarget) -> Vec<String> {
+        Fade {
+            direction,
+            start: 2.0,
+            duration: 0.5,
+            curve,
+            target,
+            high_bit_depth: false,
+        }
+        .filters()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+    }
+
+    #[test]
+    fn linear_fades_use_the_fade_filter() {
+        assert_eq!(
+            fade(FadeDirection::In, FadeCurve::Linear, FadeTarget::Alpha),
+            ["fade=t=in:st=2:d=0.5:alpha=1"]
+        );
+        assert_eq!(
+            fade(
+                FadeDirection::Out,
+                FadeCurve::Linear,
+                FadeTarget::Color("0xffffff".into())
+            ),
+            ["fade=t=out:st=2:d=0.5:color=0xffffff"]
+        );
+    }
+
+    #[test]
+    fn eased_fades_only_run_during_the_fade() {
+        let filters = fade(FadeDirection::Out, FadeCurve::EaseIn, FadeTarget::Alpha);
+        assert_eq!(filters[0], "format=gbrap");
+        assert_eq!(
+            filters[1],
+            concat!(
+                r"geq=r=r(X\,Y):g=g(X\,Y):b=b(X\,Y):",
+                r"a=alpha(X\,Y)*(st(0\,clip((T-2)/0.5\,0\,1))\;1-(ld(0)*ld(0))):enable=gte(t\,2)"
+            )
+        );
+
+        let filters = fade(
+            FadeDirection::In,
+            FadeCurve::Cubic,
+            FadeTarget::Color("0x102030".into()),
+        );
+        assert_eq!(filters[0], "format=gbrp");
+        assert!(filters[1].contains(r"+16*(1-("));
+        assert!(filters[1].contains(r"+48*(1-("));
+        assert!(filters[1].ends_with(r":enable=lte(t\,2.5)"));
+    }
+
+    #[test]
+    fn eased_fades_keep_16_bit_depth() {
+        let fade = Fade {
+            direction: FadeDirection::In,
+            start: 0.0,
+            duration: 1.0,
+            curve: FadeCurve::EaseOut,
+            target: FadeTarget::Color("0xff0000".into()),
+            high_bit_depth: true,
+        };
+        let filters = fade.filters();
+        assert_eq!(filters[0].to_string(), "format=gbrp16le");
+        assert!(filters[1].to_string().contains("+65535*(1-("));
+    }
+
+    #[test]
+    fn targets_follow_the_output_format() {
+        assert_eq!(
+            FadeTarget::for_output(true, Some("#fff")).unwrap(),
+            FadeTarget::Alpha
+        );
+        assert_eq!(
+            FadeTarget::for_output(false, None).unwrap(),
+            FadeTarget::Color("0x000000".into())
+        );
+        assert_eq!(
+            FadeTarget::for_output(false, Some("#FFFFFF")).unwrap(),
+            FadeTarget::Color("0xffffff".into())
+        );
+        assert!(FadeTarget::for_output(false, Some("white")).is_err());
+        assert!(FadeTarget::for_output(true, Some("white")).is_err());
+        assert!(FadeCurve::from_option(Some("bounce")).is_err());
+        assert_eq!(
+            FadeCurve::from_option(Some("Ease-In-Out")).unwrap(),
+            FadeCurve::EaseInOut
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt;
+
+/// Direction of a `fade` filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeDirection {
+    In,
+    Out,
+}
+
+/// One ffmpeg filter with its options, e.g. `scale=640:-1:flags=lanczos`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    name: String,
+    /// Positional (`None`) and named options, in order
+    options: Vec<(Option<String>, String)>,
+}
+
+impl Filter {
+    /// A filter without options; add them with [`arg`](Self::arg) and
+    /// [`opt`](Self::opt)
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            options: Vec::new(),
+        }
+    }
+
+    /// Add a positional option
+    pub fn arg(mut self, value: impl ToString) -> Self {
+        self.options.push((None, value.to_string()));
+        self
+    }
+
+    /// Add a `key=value` option
+    pub fn opt(mut self, key: &str, value: impl ToString) -> Self {
+        self.options
+            .push((Some(key.to_string()), value.to_string()));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Passes frames through unchanged
+    pub fn null() -> Self {
+        Self::new("null")
+    }
+
+    pub fn fps(fps: u32) -> Self {
+        Self::new("fps").arg(fps)
+    }
+
+    pub fn format(pix_fmt: &str) -> Self {
+        Self::new("format").arg(pix_fmt)
+    }
+
+    /// Scale to `width` x `height`; either may be an expression or `-1`/`-2`
+    pub fn scale(width: impl ToString, height: impl ToString) -> Self {
+        Self::new("scale").arg(width).arg(height)
+    }
+
+    /// Cut a `width` x `height` rectangle, centered unless `x`/`y` are added
+    pub fn crop(width: impl ToString, height: impl ToString) -> Self {
+        Self::new("crop").arg(width).arg(height)
+    }
+
+    /// Grow the frame to `width` x `height`, placing the input at `x`, `y`
+    pub fn pad(
+        width: impl ToString,
+        height: impl ToString,
+        x: impl ToString,
+        y: impl ToString,
+        color: &str,
+    ) -> Self {
+        Self::new("pad")
+            .arg(width)
+            .arg(height)
+            .arg(x)
+            .arg(y)
+            .opt("color", color)
+    }
+
+    pub fn fade(direction: FadeDirection, start: f32, duration: f32) -> Self {
+        let kind = match direction {
+            FadeDirection::In => "in",
+            FadeDirection::Out => "out",
+        };
+        Self::new("fade")
+            .opt("t", kind)
+            .opt("st", start)
+            .opt("d", duration)
+    }
+
+    /// Draw the second input over the first
+    pub fn overlay() -> Self {
+        Self::new("overlay")
+    }
+
+    /// Duplicate the stream into `outputs` copies
+    pub fn split(outputs: usize) -> Self {
+        Self::new("split").arg(outputs)
+    }
+
+    /// Draw a box; with `t=fill` and no size it covers the whole frame
+    pub fn drawbox(color: &str) -> Self {
+        Self::new("drawbox").opt("color", color)
+    }
+
+    /// Draw `text` at `x`, `y` (pixel expressions)
+    pub fn drawtext(text: &str, x: impl ToString, y: impl ToString) -> Self {
+        Self::new("drawtext")
+            .opt("text", text)
+            .opt("x", x)
+            .opt("y", y)
+    }
+
+    /// Read an image or video file as a source stream
+    pub fn movie(path: &str) -> Self {
+        Self::new("movie").arg(path)
+    }
+
+    pub fn setpts(expr: &str) -> Self {
+        Self::new("setpts").arg(expr)
+    }
+
+    pub fn palettegen() -> Self {
+        Self::new("palettegen")
+    }
+
+    pub fn paletteuse() -> Self {
+        Self::new("paletteuse")
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        for (i, (key, value)) in self.options.iter().enumerate() {
+            f.write_str(if i == 0 { "=" } else { ":" })?;
+            if let Some(key) = key {
+                write!(f, "{}=", key)?;
+            }
+            f.write_str(&escape_value(value))?;
+        }
+        Ok(())
+    }
+}
+
+/// Escape an option value for the option parser, then for the graph parser
+/// around it, so paths and expressions keep their `:`, `,` and quotes
+fn escape_value(value: &str) -> String {
+    let escape = |s: &str, special: &[char]| {
+        let mut escaped = String::with_capacity(s.len());
+        for c in s.chars() {
+            if special.contains(&c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    };
+    let option = escape(value, &['\\', '\'', ':']);
+    escape(&option, &['\\', '\'', '[', ']', ',', ';'])
+}
+
+/// A linear run of filters between labeled pads
+#[derive(Debug, Clone, PartialEq)]
+struct Chain {
+    inputs: Vec<String>,
+    filters: Vec<Filter>,
+    outputs: Vec<String>,
+}
+
+/// A typed ffmpeg filter graph over the first input's video stream (`[0:v]`),
+/// serialized for `-filter_complex`. Option values are escaped on output, so
+/// callers never splice filter strings by hand. The last chain is left
+/// unlabeled, so ffmpeg maps it to the output automatically.
+///
+/// Filters are appended to the open chain; [`outputs`](Self::outputs) closes
+/// it with fresh labels and [`open`](Self::open) starts a new one from
+/// labeled pads, which is how branches (splits, overlays) are built.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterGraph {
+    chains: Vec<Chain>,
+    labels: usize,
+}
+
+impl Default for FilterGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FilterGraph {
+    pub fn new() -> Self {
+        Self {
+            chains: vec![Chain {
+                inputs: vec!["0:v".into()],
+                filters: Vec::new(),
+                outputs: Vec::new(),
+            }],
+            labels: 0,
+        }
+    }
+
+    /// Whether the graph leaves the stream untouched
+    pub fn is_empty(&self) -> bool {
+        self.chains.len() == 1 && self.chains[0].filters.is_empty()
+    }
+
+    /// Append a filter to the open chain
+    pub fn push(&mut self, filter: Filter) -> &mut Self {
+        self.open_chain().filters.push(filter);
+        self
+    }
+
+    /// Append a filter to the open chain, builder style
+    pub fn then(mut self, filter: Filter) -> Self {
+        self.push(filter);
+        self
+    }
+
+    /// Insert a filter at the very start of the graph, right after `[0:v]`
+    pub fn prepend(&mut self, filter: Filter) -> &mut Self {
+        self.chains[0].filters.insert(0, filter);
+        self
+    }
+
+    /// Close the open chain with `count` new output labels and return them
+    pub fn outputs(&mut self, count: usize) -> Vec<String> {
+        let labels: Vec<String> = (0..count)
+            .map(|_| {
+                self.labels += 1;
+                format!("v{}", self.labels)
+            })
+            .collect();
+        self.open_chain().outputs = labels.clone();
+        labels
+    }
+
+    /// Start a new open chain reading the given labels (or input streams
+    /// such as `1:v`). With no inputs the chain must begin with a source
+    /// filter like `movie`.
+    pub fn open(&mut self, inputs: &[&str]) -> &mut Self {
+        self.chains.push(Chain {
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+            filters: Vec::new(),
+            outputs: Vec::new(),
+        });
+        self
+    }
+
+    fn open_chain(&mut self) -> &mut Chain {
+        self.chains
+            .last_mut()
+            .expect("a filter graph always has a chain")
+    }
+}
+
+impl fmt::Display for FilterGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, chain) in self.chains.iter().enumerate() {
+            if i > 0 {
+                f.write_str(";")?;
+            }
+            for input in &chain.inputs {
+                write!(f, "[{}]", input)?;
+            }
+            if chain.filters.is_empty() {
+                Filter::null().fmt(f)?;
+            }
+            for (j, filter) in chain.filters.iter().enumerate() {
+                if j > 0 {
+                    f.write_str(",")?;
+                }
+                filter.fmt(f)?;
+            }
+            for output in &chain.outputs {
+                write!(f, "[{}]", output)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_serialize_positional_then_named_options() {
+        assert_eq!(
+            Filter::scale(640, -1).opt("flags", "lanczos").to_string(),
+            "scale=640:-1:flags=lanczos"
+        );
+        assert_eq!(
+            Filter::fade(FadeDirection::Out, 1.5, 0.5).to_string(),
+            "fade=t=out:st=1.5:d=0.5"
+        );
+        assert_eq!(Filter::palettegen().to_string(), "palettegen");
+    }
+
+    #[test]
+    fn values_are_escaped_for_both_parsers() {
+        assert_eq!(
+            Filter::movie(r"C:\bg, v2.png").to_string(),
+            r"movie=C\\:\\\\bg\, v2.png"
+        );
+        assert_eq!(
+            Filter::new("geq")
+                .opt("lum", "if(mod(X,2),0,255)")
+                .to_string(),
+            r"geq=lum=if(mod(X\,2)\,0\,255)"
+        );
+        assert_eq!(
+            Filter::drawtext("it's 10:30", 8, "h-th-8").to_string(),
+            r"drawtext=text=it\\\'s 10\\:30:x=8:y=h-th-8"
+        );
+    }
+
+    #[test]
+    fn linear_graphs_read_the_first_input() {
+        let graph = FilterGraph::new();
+        assert!(graph.is_empty());
+        assert_eq!(graph.to_string(), "[0:v]null");
+
+        let mut graph = FilterGraph::new()
+            .then(Filter::scale(320, -2))
+            .then(Filter::fade(FadeDirection::In, 0.0, 1.0));
+        graph.prepend(Filter::fps(12));
+        assert!(!graph.is_empty());
+        assert_eq!(
+            graph.to_string(),
+            "[0:v]fps=12,scale=320:-2,fade=t=in:st=0:d=1"
+        );
+    }
+
+    #[test]
+    fn branches_get_unique_labels() {
+        let mut graph = FilterGraph::new();
+        let split = graph.push(Filter::split(2)).outputs(2);
+        let palette = graph
+            .open(&[&split[0]])
+            .push(Filter::palettegen())
+            .outputs(1);
+        graph
+            .open(&[&split[1], &palette[0]])
+            .push(Filter::paletteuse());
+        assert_eq!(
+            graph.to_string(),
+            "[0:v]split=2[v1][v2];[v1]palettegen[v3];[v2][v3]paletteuse"
+        );
+    }
+}
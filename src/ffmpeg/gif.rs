@@ -1,63 +1,162 @@
-use std::fs;
 use std::path::PathBuf;
+use tempfile::tempdir;
 
+use crate::cancel::CancellationToken;
+use crate::config::RenderConfig;
+use crate::error::RenderError;
+use crate::ffmpeg::filter::{Filter, FilterGraph};
+use crate::ffmpeg::source::FrameSource;
+use crate::observer::{RenderObserver, RenderStage};
 use crate::report::RenderReport;
 use crate::utils;
 
-/// Render a GIF using palettegen + paletteuse filters
+const DITHER_MODES: &[&str] = &[
+    "bayer",
+    "heckbert",
+    "floyd_steinberg",
+    "sierra2",
+    "sierra2_4a",
+    "sierra3",
+    "burkes",
+    "atkinson",
+    "none",
+];
+
+const STATS_MODES: &[&str] = &["full", "diff", "single"];
+
+/// Width used when neither `width` nor `height` is configured
+pub const DEFAULT_WIDTH: u32 = 640;
+
+/// GIF encoding options, usually taken from a [`RenderConfig`]
+#[derive(Debug, Clone, Default)]
+pub struct GifOptions {
+    pub max_colors: Option<u32>,
+    pub dither: Option<String>,
+    pub stats_mode: Option<String>,
+    pub reserve_transparent: Option<bool>,
+    pub loop_count: Option<u32>,
+}
+
+impl GifOptions {
+    pub fn from_config(config: &RenderConfig) -> Self {
+        Self {
+            max_colors: config.max_colors,
+            dither: config.dither.clone(),
+            stats_mode: config.stats_mode.clone(),
+            reserve_transparent: config.reserve_transparent,
+            loop_count: config.loop_count,
+        }
+    }
+
+    fn palettegen_filter(&self) -> Result<Filter, RenderError> {
+        let max_colors = self.max_colors.unwrap_or(256);
+        if !(2..=256).contains(&max_colors) {
+            return Err(RenderError::InvalidOption {
+                name: "max_colors",
+                value: max_colors.to_string(),
+            });
+        }
+        let stats_mode = checked_option("stats_mode", self.stats_mode.as_deref(), STATS_MODES)?
+            .unwrap_or("full");
+        let reserve = self.reserve_transparent.unwrap_or(true);
+        Ok(Filter::palettegen()
+            .opt("max_colors", max_colors)
+            .opt("stats_mode", stats_mode)
+            .opt("reserve_transparent", reserve as u8))
+    }
+
+    fn paletteuse_filter(&self) -> Result<Filter, RenderError> {
+        let dither =
+            checked_option("dither", self.dither.as_deref(), DITHER_MODES)?.unwrap_or("sierra2_4a");
+        let mut filter = Filter::paletteuse().opt("dither", dither);
+        // A per-frame diff palette only pays off when paletteuse also limits updates
+        if self.stats_mode.as_deref() == Some("diff") {
+            filter = filter.opt("diff_mode", "rectangle");
+        }
+        Ok(filter)
+    }
+
+    /// The GIF muxer's `-loop` counts repeats after the first play, with -1
+    /// meaning play once and 0 meaning forever
+    fn loop_arg(&self) -> i64 {
+        match self.loop_count {
+            None | Some(0) => 0,
+            Some(1) => -1,
+            Some(n) => i64::from(n) - 1,
+        }
+    }
+}
+
+fn checked_option<'a>(
+    name: &'static str,
+    value: Option<&'a str>,
+    allowed: &[&str],
+) -> Result<Option<&'a str>, RenderError> {
+    match value {
+        Some(v) if !allowed.contains(&v) => Err(RenderError::InvalidOption {
+            name,
+            value: v.to_string(),
+        }),
+        _ => Ok(value),
+    }
+}
+
+/// Render a GIF using palettegen + paletteuse filters.
+///
+/// The fps filter and `filters` (resize, fades) run in both passes, so the
+/// palette is computed from exactly the frames that get encoded. The palette lives in a
+/// per-render temp directory, so concurrent GIF renders never clash.
+#[allow(clippy::too_many_arguments)]
 pub fn render_gif(
-    input_pattern: &str,
+    source: &FrameSource,
     output: &str,
     fps: u32,
-    fade_filter: Option<&str>,
+    options: &GifOptions,
+    filters: &FilterGraph,
     verbose_ffmpeg: bool,
-) -> Result<RenderReport, String> {
-    let palette_path = "palette.png";
-
-    // ----- 1. Generate palette -----
-    let mut palette_args: Vec<String> = Vec::new();
-    if input_pattern.contains('*') {
-        palette_args.push("-pattern_type".into());
-        palette_args.push("glob".into());
-    }
-    palette_args.push("-i".into());
-    palette_args.push(input_pattern.into());
-    palette_args.push("-vf".into());
-    palette_args.push("fps=30,scale=640:-1:flags=lanczos,palettegen".into());
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<RenderReport, RenderError> {
+    let palette_dir =
+        tempdir().map_err(|e| RenderError::io("Failed to create palette temp dir", e))?;
+    let palette_path = palette_dir.path().join("palette.png");
+    let palette_path = palette_path.to_string_lossy();
+
+    // ----- 1. Build filter graphs -----
+    let mut frames = filters.clone();
+    frames.prepend(Filter::fps(fps));
+    let mut palette_graph = frames.clone();
+    palette_graph.push(options.palettegen_filter()?);
+    let mut gif_graph = frames;
+    let gif_frames = gif_graph.outputs(1);
+    gif_graph
+        .open(&[&gif_frames[0], "1:v"])
+        .push(options.paletteuse_filter()?);
+
+    // ----- 2. Generate palette -----
+    let mut palette_args = source.input_args(fps);
+    palette_args.push("-filter_complex".into());
+    palette_args.push(palette_graph.to_string());
     palette_args.push("-y".into());
-    palette_args.push(palette_path.into());
+    palette_args.push(palette_path.to_string());
     if !verbose_ffmpeg {
         palette_args.push("-loglevel".into());
         palette_args.push("warning".into());
     }
 
-    let palette_stderr = match utils::run_ffmpeg_with_output(&palette_args) {
-        Ok((_, stderr)) => stderr,
-        Err(e) => return Err(format!("❌ Failed to run ffmpeg for palettegen: {}", e)),
-    };
+    observer.on_stage(RenderStage::GeneratingPalette);
+    let (_, palette_stderr) =
+        utils::run_ffmpeg_with_input(&palette_args, source.stdin_feed(), &mut |_| {}, cancel)?;
     let _palette_warnings = utils::scan_ffmpeg_stderr(&palette_stderr);
 
-    // ----- 2. Build filter chain -----
-    let mut gif_filter = String::from("fps=30,scale=640:-1:flags=lanczos");
-    if let Some(filter) = fade_filter {
-        if !filter.is_empty() {
-            gif_filter.push(',');
-            gif_filter.push_str(filter);
-        }
-    }
-
     // ----- 3. Render final GIF -----
-    let mut gif_args: Vec<String> = vec!["-framerate".into(), fps.to_string()];
-    if input_pattern.contains('*') {
-        gif_args.push("-pattern_type".into());
-        gif_args.push("glob".into());
-    }
+    let mut gif_args = source.input_args(fps);
     gif_args.push("-i".into());
-    gif_args.push(input_pattern.into());
-    gif_args.push("-i".into());
-    gif_args.push(palette_path.into());
-    gif_args.push("-lavfi".into());
-    gif_args.push(format!("{} [x]; [x][1:v] paletteuse", gif_filter));
+    gif_args.push(palette_path.to_string());
+    gif_args.push("-filter_complex".into());
+    gif_args.push(gif_graph.to_string());
+    gif_args.push("-loop".into());
+    gif_args.push(options.loop_arg().to_string());
     gif_args.push("-y".into());
     gif_args.push(output.into());
     if !verbose_ffmpeg {
@@ -65,21 +164,77 @@ pub fn render_gif(
         gif_args.push("warning".into());
     }
 
-    let gif_stderr = match utils::run_ffmpeg_with_output(&gif_args) {
-        Ok((_, stderr)) => stderr,
-        Err(e) => return Err(format!("❌ Failed to render final GIF: {}", e)),
-    };
+    observer.on_stage(RenderStage::Encoding);
+    let (_, gif_stderr) = utils::run_ffmpeg_with_input(
+        &gif_args,
+        source.stdin_feed(),
+        &mut |p| observer.on_frame_progress(p),
+        cancel,
+    )
+    .inspect_err(|e| utils::remove_partial_output(e, output))?;
     let _gif_warnings = utils::scan_ffmpeg_stderr(&gif_stderr);
 
-    // ----- 4. Clean up -----
-    fs::remove_file(palette_path)
-        .unwrap_or_else(|e| eprintln!("⚠️ Failed to remove palette file: {}", e));
-
     Ok(RenderReport {
         output_path: PathBuf::from(output),
         frames_rendered: None,
         ffmpeg_warnings: _gif_warnings,
         preview: false,
         notes: Some("GIF export via palettegen".into()),
+        sequence_issues: Vec::new(),
+        frame_check: None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::GifOptions;
+
+    #[test]
+    fn defaults_match_previous_output() {
+        let options = GifOptions::default();
+        assert_eq!(
+            options.palettegen_filter().unwrap().to_string(),
+            "palettegen=max_colors=256:stats_mode=full:reserve_transparent=1"
+        );
+        assert_eq!(
+            options.paletteuse_filter().unwrap().to_string(),
+            "paletteuse=dither=sierra2_4a"
+        );
+        assert_eq!(options.loop_arg(), 0);
+    }
+
+    #[test]
+    fn options_drive_filters() {
+        let options = GifOptions {
+            max_colors: Some(64),
+            dither: Some("bayer".into()),
+            stats_mode: Some("diff".into()),
+            reserve_transparent: Some(false),
+            loop_count: Some(3),
+        };
+        assert_eq!(
+            options.palettegen_filter().unwrap().to_string(),
+            "palettegen=max_colors=64:stats_mode=diff:reserve_transparent=0"
+        );
+        assert_eq!(
+            options.paletteuse_filter().unwrap().to_string(),
+            "paletteuse=dither=bayer:diff_mode=rectangle"
+        );
+        assert_eq!(options.loop_arg(), 2);
+    }
+
+    #[test]
+    fn rejects_unknown_modes() {
+        let options = GifOptions {
+            dither: Some("random".into()),
+            ..Default::default()
+        };
+        assert!(options.paletteuse_filter().is_err());
+
+        let options = GifOptions {
+            max_colors: Some(512),
+            ..Default::default()
+        };
+        assert!(options.palettegen_filter().is_err());
+    }
+}
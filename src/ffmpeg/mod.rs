@@ -1,3 +1,11 @@
+pub mod apng;
+pub mod background;
+pub mod fade;
+pub mod filter;
 pub mod gif;
+pub mod probe;
+pub mod source;
+pub mod transform;
 /// FFmpeg rendering helpers for different output formats
 pub mod video;
+pub mod webp;
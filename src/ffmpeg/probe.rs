@@ -0,0 +1,119 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::RenderError;
+
+/// What ffprobe reports about the first video stream of a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoInfo {
+    pub codec: String,
+    pub width: u32,
+    pub height: u32,
+    /// Frames per second, from `avg_frame_rate`
+    pub fps: Option<f64>,
+    /// Duration in seconds
+    pub duration: Option<f64>,
+    /// Frame count from the container, or estimated from duration and fps
+    pub frame_count: Option<usize>,
+}
+
+impl VideoInfo {
+    /// Decoder to force for this stream. ffmpeg's native VP8/VP9 decoders
+    /// drop the alpha plane, libvpx keeps it.
+    pub fn decoder(&self) -> Option<&'static str> {
+        match self.codec.as_str() {
+            "vp8" => Some("libvpx"),
+            "vp9" => Some("libvpx-vp9"),
+            _ => None,
+        }
+    }
+}
+
+/// Probe `path` with ffprobe. Returns `None` when the file is not a video,
+/// including single images and image sequences.
+pub fn probe_video(path: &Path) -> Result<Option<VideoInfo>, RenderError> {
+    let output = Command::new("ffprobe")
+        .args([
+            "-v",
+            "error",
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "stream=codec_name,width,height,avg_frame_rate,nb_frames:format=format_name,duration",
+            "-of",
+            "default=noprint_wrappers=1",
+        ])
+        .arg(path)
+        .output()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                RenderError::FfprobeNotFound
+            } else {
+                RenderError::io("Failed to execute ffprobe", e)
+            }
+        })?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(parse_probe_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parse `key=value` lines printed by [`probe_video`]
+fn parse_probe_output(text: &str) -> Option<VideoInfo> {
+    let value = |key: &str| {
+        text.lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(k, v)| *k == key && *v != "N/A")
+            .map(|(_, v)| v.trim())
+    };
+
+    // Images are "videos" to ffprobe, read through the image2 family
+    let format_name = value("format_name")?;
+    if format_name == "image2" || format_name.ends_with("_pipe") {
+        return None;
+    }
+
+    let fps = value("avg_frame_rate").and_then(|rate| {
+        let (num, den) = rate.split_once('/')?;
+        let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+        (num > 0.0 && den > 0.0).then(|| num / den)
+    });
+    let duration: Option<f64> = value("duration").and_then(|d| d.parse().ok());
+    let frame_count = value("nb_frames")
+        .and_then(|n| n.parse().ok())
+        .or_else(|| Some((duration? * fps?).round() as usize));
+
+    Some(VideoInfo {
+        codec: value("codec_name")?.to_string(),
+        width: value("width")?.parse().ok()?,
+        height: value("height")?.parse().ok()?,
+        fps,
+        duration,
+        frame_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_probe_output;
+
+    #[test]
+    fn parses_webm_without_frame_count() {
+        let info = parse_probe_output(
+            "codec_name=vp9\nwidth=640\nheight=360\navg_frame_rate=30/1\nnb_frames=N/A\n\
+             format_name=matroska,webm\nduration=2.000000\n",
+        )
+        .unwrap();
+        assert_eq!(info.codec, "vp9");
+        assert_eq!((info.width, info.height), (640, 360));
+        assert_eq!(info.fps, Some(30.0));
+        assert_eq!(info.frame_count, Some(60));
+        assert_eq!(info.decoder(), Some("libvpx-vp9"));
+    }
+
+    #[test]
+    fn images_are_not_videos() {
+        let png = "codec_name=png\nwidth=8\nheight=8\navg_frame_rate=0/0\nformat_name=png_pipe\n";
+        assert!(parse_probe_output(png).is_none());
+    }
+}
@@ -0,0 +1,602 @@
+use std::fs::File;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use zip::ZipArchive;
+
+use crate::cancel::CancellationToken;
+use crate::error::RenderError;
+use crate::ffmpeg::probe::VideoInfo;
+use crate::input::ImageFormat;
+use crate::utils::StdinFeed;
+
+/// Where ffmpeg reads input frames from
+#[derive(Debug, Clone)]
+pub enum FrameSource {
+    /// Frames on disk matched by a glob (`*.png`) or sequence (`%04d.png`) pattern
+    Pattern(String),
+    /// Files on disk, piped to ffmpeg's stdin in exactly the given order (a
+    /// file may repeat, e.g. to hold a frame over a gap in the sequence)
+    Files(Vec<PathBuf>),
+    /// Entries of a ZIP archive, streamed to ffmpeg's stdin in the given order
+    /// without extracting them to disk
+    ZipStream {
+        archive: PathBuf,
+        entries: Vec<usize>,
+        format: ImageFormat,
+    },
+    /// Frames produced in memory and piped to ffmpeg's stdin
+    Memory(MemoryFrames),
+    /// An existing video file, re-encoded at its own frame rate
+    Video { path: PathBuf, info: VideoInfo },
+}
+
+/// Layout of in-memory frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameLayout {
+    /// Each item is an encoded PNG image
+    Png,
+    /// Each item is `width * height * 4` bytes of 8-bit RGBA
+    Rgba { width: u32, height: u32 },
+}
+
+type FrameIter = Peekable<Box<dyn Iterator<Item = Vec<u8>> + Send>>;
+
+#[derive(Clone)]
+enum FrameStore {
+    /// Streamed as produced; only one ffmpeg pass can read them
+    Once(Arc<Mutex<Option<FrameIter>>>),
+    /// Held in memory, so multi-pass renders (GIF palettes) see every frame
+    Buffered(Arc<Vec<Vec<u8>>>),
+}
+
+/// Frames produced in memory, e.g. by a generative-art service, rendered
+/// without writing intermediate files. Cloning shares the same frames.
+#[derive(Clone)]
+pub struct MemoryFrames {
+    layout: FrameLayout,
+    store: FrameStore,
+    /// Exact frame count, when known up front
+    count: Option<usize>,
+    /// Frames written to ffmpeg by the latest pass
+    fed: Arc<AtomicUsize>,
+}
+
+impl MemoryFrames {
+    /// Encoded PNG images, one per frame
+    pub fn png<I>(frames: I) -> Self
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+        I::IntoIter: Send + 'static,
+    {
+        Self::new(FrameLayout::Png, frames)
+    }
+
+    /// Raw 8-bit RGBA buffers of `width * height * 4` bytes, one per frame
+    pub fn rgba<I>(width: u32, height: u32, frames: I) -> Self
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+        I::IntoIter: Send + 'static,
+    {
+        Self::new(FrameLayout::Rgba { width, height }, frames)
+    }
+
+    fn new<I>(layout: FrameLayout, frames: I) -> Self
+    where
+        I: IntoIterator<Item = Vec<u8>>,
+        I::IntoIter: Send + 'static,
+    {
+        let frames = frames.into_iter();
+        let count = match frames.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower),
+            _ => None,
+        };
+        let frames: Box<dyn Iterator<Item = Vec<u8>> + Send> = Box::new(frames);
+        Self {
+            layout,
+            store: FrameStore::Once(Arc::new(Mutex::new(Some(frames.peekable())))),
+            count,
+            fed: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn layout(&self) -> FrameLayout {
+        self.layout
+    }
+
+    /// Exact number of frames, if known without consuming them
+    pub fn count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// Whether there are no frames left to render. Streamed frames of
+    /// unknown length are peeked at without being consumed.
+    pub fn is_empty(&self) -> bool {
+        if let Some(count) = self.count {
+            return count == 0;
+        }
+        match &self.store {
+            FrameStore::Buffered(all) => all.is_empty(),
+            FrameStore::Once(iter) => iter
+                .lock()
+                .ok()
+                .and_then(|mut iter| iter.as_mut().map(|iter| iter.peek().is_none()))
+                .unwrap_or(true),
+        }
+    }
+
+    /// Why an RGBA frame cannot be fed, if its length doesn't match the size
+    fn size_problem(&self, index: usize, frame: &[u8]) -> Option<String> {
+        let FrameLayout::Rgba { width, height } = self.layout else {
+            return None;
+        };
+        let expected = width as usize * height as usize * 4;
+        (frame.len() != expected).then(|| {
+            format!(
+                "RGBA frame {} has {} bytes, expected {} for {}x{}",
+                index,
+                frame.len(),
+                expected,
+                width,
+                height
+            )
+        })
+    }
+
+    /// Check the size of every buffered RGBA frame. Streamed frames are
+    /// checked as they are fed instead.
+    pub(crate) fn check_buffered(&self) -> Result<(), RenderError> {
+        let FrameStore::Buffered(all) = &self.store else {
+            return Ok(());
+        };
+        let problems: Vec<_> = all
+            .iter()
+            .enumerate()
+            .filter_map(|(index, frame)| self.size_problem(index, frame))
+            .collect();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(RenderError::CorruptFrames(problems))
+        }
+    }
+
+    /// A copy of the first frame, without consuming streamed frames
+    pub(crate) fn first(&self) -> Option<Vec<u8>> {
+        match &self.store {
+            FrameStore::Buffered(all) => all.first().cloned(),
+            FrameStore::Once(iter) => iter
+                .lock()
+                .ok()
+                .and_then(|mut iter| iter.as_mut().and_then(|iter| iter.peek().cloned())),
+        }
+    }
+
+    /// Frames written to ffmpeg by the most recent pass
+    pub fn frames_fed(&self) -> usize {
+        self.fed.load(Ordering::Relaxed)
+    }
+
+    /// Collect streamed frames into memory so they can be read more than once
+    pub fn buffered(self) -> Self {
+        let frames = match &self.store {
+            FrameStore::Buffered(_) => return self,
+            FrameStore::Once(iter) => iter
+                .lock()
+                .ok()
+                .and_then(|mut iter| iter.take())
+                .map(|iter| iter.collect::<Vec<_>>())
+                .unwrap_or_default(),
+        };
+        Self {
+            count: Some(frames.len()),
+            store: FrameStore::Buffered(Arc::new(frames)),
+            ..self
+        }
+    }
+
+    fn input_args(&self) -> Vec<String> {
+        match self.layout {
+            FrameLayout::Png => vec!["-f".into(), "png_pipe".into()],
+            FrameLayout::Rgba { width, height } => vec![
+                "-f".into(),
+                "rawvideo".into(),
+                "-pix_fmt".into(),
+                "rgba".into(),
+                "-s".into(),
+                format!("{}x{}", width, height),
+            ],
+        }
+    }
+
+    fn stdin_feed(&self) -> StdinFeed {
+        let frames = self.clone();
+        Box::new(move |stdin| {
+            frames.fed.store(0, Ordering::Relaxed);
+            let mut write = |index: usize, frame: &[u8]| {
+                if let Some(problem) = frames.size_problem(index, frame) {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        RenderError::CorruptFrames(vec![problem]),
+                    ));
+                }
+                stdin.write_all(frame)?;
+                frames.fed.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            };
+            match &frames.store {
+                FrameStore::Buffered(all) => {
+                    for (index, frame) in all.iter().enumerate() {
+                        write(index, frame)?;
+                    }
+                }
+                FrameStore::Once(iter) => {
+                    let iter = iter.lock().ok().and_then(|mut iter| iter.take());
+                    for (index, frame) in iter.into_iter().flatten().enumerate() {
+                        write(index, &frame)?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+impl std::fmt::Debug for MemoryFrames {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryFrames")
+            .field("layout", &self.layout)
+            .field("count", &self.count)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FrameSource {
+    /// Input arguments for ffmpeg, up to and including `-i`
+    pub fn input_args(&self, fps: u32) -> Vec<String> {
+        match self {
+            FrameSource::Pattern(pattern) => {
+                let mut args = self.decoder_args();
+                args.extend(["-framerate".into(), fps.to_string()]);
+                if pattern.contains('*') {
+                    args.push("-pattern_type".into());
+                    args.push("glob".into());
+                }
+                args.push("-i".into());
+                args.push(pattern.clone());
+                args
+            }
+            FrameSource::Memory(frames) => {
+                let mut args = frames.input_args();
+                args.extend([
+                    "-framerate".into(),
+                    fps.to_string(),
+                    "-i".into(),
+                    "pipe:0".into(),
+                ]);
+                args
+            }
+            FrameSource::Video { path, info } => {
+                let mut args = Vec::new();
+                if let Some(decoder) = info.decoder() {
+                    args.extend(["-c:v".into(), decoder.into()]);
+                }
+                args.extend(["-i".into(), path.to_string_lossy().into_owned()]);
+                args
+            }
+            FrameSource::Files(_) | FrameSource::ZipStream { .. } => {
+                let mut args = vec![
+                    "-f".into(),
+                    self.format()
+                        .and_then(ImageFormat::pipe_demuxer)
+                        .unwrap_or("image2pipe")
+                        .into(),
+                ];
+                args.extend(self.decoder_args());
+                args.extend([
+                    "-framerate".into(),
+                    fps.to_string(),
+                    "-i".into(),
+                    "pipe:0".into(),
+                ]);
+                args
+            }
+        }
+    }
+
+    /// Per-format decoder options for image frames
+    fn decoder_args(&self) -> Vec<String> {
+        self.format()
+            .map(ImageFormat::decoder_args)
+            .unwrap_or_default()
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect()
+    }
+
+    /// Writer that feeds ffmpeg's stdin, for sources that are piped
+    pub fn stdin_feed(&self) -> Option<StdinFeed> {
+        match self {
+            FrameSource::Pattern(_) | FrameSource::Video { .. } => None,
+            FrameSource::Memory(frames) => Some(frames.stdin_feed()),
+            FrameSource::Files(files) => {
+                let files = files.clone();
+                Some(Box::new(move |stdin| {
+                    for path in &files {
+                        std::io::copy(&mut File::open(path)?, stdin)?;
+                    }
+                    Ok(())
+                }))
+            }
+            FrameSource::ZipStream {
+                archive, entries, ..
+            } => {
+                let archive = archive.clone();
+                let entries = entries.clone();
+                Some(Box::new(move |stdin| {
+                    let mut zip =
+                        ZipArchive::new(File::open(&archive)?).map_err(std::io::Error::other)?;
+                    for &index in &entries {
+                        let mut entry = zip.by_index(index).map_err(std::io::Error::other)?;
+                        std::io::copy(&mut entry, stdin)?;
+                    }
+                    Ok(())
+                }))
+            }
+        }
+    }
+}
+
+impl FrameSource {
+    /// Image format of the frames, when known
+    pub fn format(&self) -> Option<ImageFormat> {
+        match self {
+            FrameSource::Pattern(pattern) => ImageFormat::from_path(Path::new(pattern)),
+            FrameSource::Files(files) => files.first().and_then(|f| ImageFormat::from_path(f)),
+            FrameSource::ZipStream { format, .. } => Some(*format),
+            FrameSource::Video { .. } => None,
+            FrameSource::Memory(frames) => match frames.layout() {
+                FrameLayout::Png => Some(ImageFormat::Png),
+                FrameLayout::Rgba { .. } => None,
+            },
+        }
+    }
+
+    /// Whether the frames cannot be piped and must be staged on disk first
+    pub fn needs_staging(&self) -> bool {
+        !matches!(self, FrameSource::Pattern(_))
+            && self.format().is_some_and(|f| f.pipe_demuxer().is_none())
+    }
+
+    /// Write the frames into `dir` as a numbered sequence (`000000.tga`, ...)
+    /// and return a pattern source reading them back in the same order.
+    /// Stops with [`RenderError::Cancelled`] once `cancel` is triggered.
+    pub fn stage(
+        &self,
+        dir: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<FrameSource, RenderError> {
+        let ext = self.format().map(ImageFormat::extension).unwrap_or("png");
+        let staged = |i: usize| dir.join(format!("{:06}.{}", i, ext));
+        let failed = |e| RenderError::io("Failed to stage input frames", e);
+        match self {
+            FrameSource::Pattern(_) | FrameSource::Memory(_) | FrameSource::Video { .. } => {
+                return Ok(self.clone())
+            }
+            FrameSource::Files(files) => {
+                for (i, path) in files.iter().enumerate() {
+                    cancel.check()?;
+                    // Hard links are free; fall back to copying across devices
+                    if std::fs::hard_link(path, staged(i)).is_err() {
+                        std::fs::copy(path, staged(i)).map_err(failed)?;
+                    }
+                }
+            }
+            FrameSource::ZipStream {
+                archive, entries, ..
+            } => {
+                let mut zip = ZipArchive::new(File::open(archive).map_err(failed)?)?;
+                for (i, &index) in entries.iter().enumerate() {
+                    cancel.check()?;
+                    let mut entry = zip.by_index(index)?;
+                    let mut out = File::create(staged(i)).map_err(failed)?;
+                    std::io::copy(&mut entry, &mut out).map_err(failed)?;
+                }
+            }
+        }
+        let pattern = dir.join(format!("%06d.{}", ext));
+        Ok(FrameSource::Pattern(pattern.to_string_lossy().into()))
+    }
+}
+
+impl std::fmt::Display for FrameSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameSource::Pattern(pattern) => f.write_str(pattern),
+            FrameSource::Video { path, info } => {
+                write!(f, "{} ({} video)", path.display(), info.codec)
+            }
+            FrameSource::Memory(frames) => match frames.count() {
+                Some(count) => write!(f, "memory ({} frames)", count),
+                None => f.write_str("memory"),
+            },
+            FrameSource::Files(files) => {
+                let dir = files
+                    .first()
+                    .and_then(|p| p.parent())
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default();
+                write!(f, "{} ({} frames streamed)", dir, files.len())
+            }
+            FrameSource::ZipStream {
+                archive, entries, ..
+            } => write!(
+                f,
+                "{} ({} frames streamed)",
+                archive.display(),
+                entries.len()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameSource;
+    use crate::cancel::CancellationToken;
+    use crate::input::ImageFormat;
+
+    #[test]
+    fn glob_patterns_enable_glob_mode() {
+        let args = FrameSource::Pattern("frames/*.png".into()).input_args(24);
+        assert_eq!(
+            args,
+            [
+                "-framerate",
+                "24",
+                "-pattern_type",
+                "glob",
+                "-i",
+                "frames/*.png"
+            ]
+        );
+
+        let args = FrameSource::Pattern("frames/frame_%04d.png".into()).input_args(24);
+        assert_eq!(args, ["-framerate", "24", "-i", "frames/frame_%04d.png"]);
+    }
+
+    #[test]
+    fn zip_streams_through_image2pipe() {
+        let source = FrameSource::ZipStream {
+            archive: "frames.zip".into(),
+            entries: vec![0, 1],
+            format: ImageFormat::Jpeg,
+        };
+        assert_eq!(
+            source.input_args(30),
+            ["-f", "jpeg_pipe", "-framerate", "30", "-i", "pipe:0"]
+        );
+        assert!(source.stdin_feed().is_some());
+    }
+
+    #[test]
+    fn exr_frames_are_decoded_to_srgb() {
+        let source = FrameSource::Files(vec!["render/frame_0001.exr".into()]);
+        assert_eq!(
+            source.input_args(24),
+            [
+                "-f",
+                "exr_pipe",
+                "-apply_trc",
+                "iec61966_2_1",
+                "-framerate",
+                "24",
+                "-i",
+                "pipe:0"
+            ]
+        );
+        let staged = FrameSource::Pattern("staged/%06d.exr".into()).input_args(24);
+        assert_eq!(&staged[..2], ["-apply_trc", "iec61966_2_1"]);
+    }
+
+    #[test]
+    fn files_are_piped_in_list_order() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let a = dir.path().join("frame_10.png");
+        let b = dir.path().join("frame_2.png");
+        std::fs::write(&a, b"ten")?;
+        std::fs::write(&b, b"two")?;
+
+        let source = FrameSource::Files(vec![b.clone(), a, b]);
+        assert_eq!(
+            source.input_args(12),
+            ["-f", "png_pipe", "-framerate", "12", "-i", "pipe:0"]
+        );
+        let mut piped = Vec::new();
+        (source.stdin_feed().unwrap())(&mut piped)?;
+        assert_eq!(piped, b"twotentwo");
+        Ok(())
+    }
+
+    #[test]
+    fn tga_frames_are_staged_as_a_sequence() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let frame = dir.path().join("shot_7.tga");
+        std::fs::write(&frame, b"tga")?;
+        let source = FrameSource::Files(vec![frame.clone(), frame]);
+        assert!(source.needs_staging());
+
+        let staging = tempfile::tempdir()?;
+        let staged = source.stage(staging.path(), &CancellationToken::new())?;
+        assert!(!staged.needs_staging());
+        assert!(staged.to_string().ends_with("%06d.tga"));
+        assert_eq!(std::fs::read(staging.path().join("000001.tga"))?, b"tga");
+        Ok(())
+    }
+
+    #[test]
+    fn memory_frames_stream_raw_rgba() -> Result<(), Box<dyn std::error::Error>> {
+        let frames = super::MemoryFrames::rgba(1, 1, vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]);
+        assert_eq!(frames.count(), Some(2));
+        let source = FrameSource::Memory(frames.clone());
+        assert_eq!(
+            source.input_args(25),
+            [
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgba",
+                "-s",
+                "1x1",
+                "-framerate",
+                "25",
+                "-i",
+                "pipe:0"
+            ]
+        );
+        let mut piped = Vec::new();
+        (source.stdin_feed().unwrap())(&mut piped)?;
+        assert_eq!(piped, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(frames.frames_fed(), 2);
+
+        // Streamed frames are consumed by the first pass
+        let mut again = Vec::new();
+        (source.stdin_feed().unwrap())(&mut again)?;
+        assert!(again.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn empty_streams_are_detected_without_consuming_frames(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let empty = super::MemoryFrames::png((0..3).map(|_| vec![1u8]).filter(|_| false));
+        assert_eq!(empty.count(), None);
+        assert!(empty.is_empty());
+
+        let frames = super::MemoryFrames::png((0..3).map(|i| vec![i]).filter(|_| true));
+        assert!(!frames.is_empty());
+        let mut piped = Vec::new();
+        (FrameSource::Memory(frames.clone()).stdin_feed().unwrap())(&mut piped)?;
+        assert_eq!(piped, [0, 1, 2]);
+        assert!(frames.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn buffered_memory_frames_replay_and_check_size() {
+        let frames = super::MemoryFrames::rgba(2, 1, (0..2).map(|_| vec![0u8; 8])).buffered();
+        let source = FrameSource::Memory(frames);
+        for _ in 0..2 {
+            let mut piped = Vec::new();
+            (source.stdin_feed().unwrap())(&mut piped).unwrap();
+            assert_eq!(piped.len(), 16);
+        }
+
+        let bad = FrameSource::Memory(super::MemoryFrames::rgba(2, 2, vec![vec![0u8; 3]]));
+        assert!(!super::MemoryFrames::rgba(2, 2, vec![vec![0u8; 3]]).is_empty());
+        let err = (bad.stdin_feed().unwrap())(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
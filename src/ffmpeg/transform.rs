@@ -0,0 +1,391 @@
+use crate::config::RenderConfig;
+use crate::error::RenderError;
+use crate::ffmpeg::filter::Filter;
+
+pub(crate) const SCALE_FLAGS: &[&str] = &[
+    "fast_bilinear",
+    "bilinear",
+    "bicubic",
+    "experimental",
+    "neighbor",
+    "area",
+    "bicublin",
+    "gauss",
+    "sinc",
+    "lanczos",
+    "spline",
+];
+
+/// Color used for padding, transparent where the output keeps alpha
+const PAD_COLOR: &str = "black@0";
+
+/// How frames are fitted into a `width` x `height` box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Fit {
+    /// Scale to fit inside the box and pad the rest
+    Contain,
+    /// Scale to fill the box and crop the overflow
+    Cover,
+    /// Scale to the exact box, ignoring the aspect ratio
+    #[default]
+    Stretch,
+}
+
+impl Fit {
+    pub fn from_option(value: Option<&str>) -> Result<Self, RenderError> {
+        match value {
+            None => Ok(Fit::default()),
+            Some(v) => match v.to_lowercase().as_str() {
+                "contain" => Ok(Fit::Contain),
+                "cover" => Ok(Fit::Cover),
+                "stretch" => Ok(Fit::Stretch),
+                _ => Err(RenderError::InvalidOption {
+                    name: "fit",
+                    value: v.to_string(),
+                }),
+            },
+        }
+    }
+}
+
+/// A rectangle cut from the source frames, before any scaling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crop {
+    pub width: u32,
+    pub height: u32,
+    /// Offset from the left edge; centered when unset
+    pub x: Option<u32>,
+    /// Offset from the top edge; centered when unset
+    pub y: Option<u32>,
+}
+
+impl Crop {
+    /// Parse `W:H` (centered) or `W:H:X:Y`
+    pub fn parse(value: &str) -> Result<Self, RenderError> {
+        let invalid = || RenderError::InvalidOption {
+            name: "crop",
+            value: value.to_string(),
+        };
+        let parts = parse_numbers(value).ok_or_else(invalid)?;
+        match parts[..] {
+            [width, height] if width > 0 && height > 0 => Ok(Self {
+                width,
+                height,
+                x: None,
+                y: None,
+            }),
+            [width, height, x, y] if width > 0 && height > 0 => Ok(Self {
+                width,
+                height,
+                x: Some(x),
+                y: Some(y),
+            }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Margins added around the frames after scaling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Pad {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Pad {
+    /// Parse CSS-style margins: `ALL`, `VERTICAL:HORIZONTAL` or
+    /// `TOP:RIGHT:BOTTOM:LEFT`
+    pub fn parse(value: &str) -> Result<Self, RenderError> {
+        let parts = parse_numbers(value).ok_or_else(|| RenderError::InvalidOption {
+            name: "pad",
+            value: value.to_string(),
+        })?;
+        let (top, right, bottom, left) = match parts[..] {
+            [all] => (all, all, all, all),
+            [v, h] => (v, h, v, h),
+            [top, right, bottom, left] => (top, right, bottom, left),
+            _ => {
+                return Err(RenderError::InvalidOption {
+                    name: "pad",
+                    value: value.to_string(),
+                })
+            }
+        };
+        Ok(Self {
+            top,
+            right,
+            bottom,
+            left,
+        })
+    }
+}
+
+fn parse_numbers(value: &str) -> Option<Vec<u32>> {
+    value
+        .split(':')
+        .map(|part| part.trim().parse().ok())
+        .collect()
+}
+
+/// Crop, resize and pad options, compiled into ffmpeg filters that run
+/// before fades and encoding
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transform {
+    pub crop: Option<Crop>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fit: Fit,
+    pub pad: Option<Pad>,
+    pub scale_flags: Option<String>,
+}
+
+impl Transform {
+    pub fn from_config(config: &RenderConfig) -> Result<Self, RenderError> {
+        if let Some(flags) = config.scale_flags.as_deref() {
+            if !SCALE_FLAGS.contains(&flags) {
+                return Err(RenderError::InvalidOption {
+                    name: "scale_flags",
+                    value: flags.to_string(),
+                });
+            }
+        }
+        for (name, size) in [("width", config.width), ("height", config.height)] {
+            if size == Some(0) {
+                return Err(RenderError::InvalidOption {
+                    name,
+                    value: "0".into(),
+                });
+            }
+        }
+        Ok(Self {
+            crop: config.crop.as_deref().map(Crop::parse).transpose()?,
+            width: config.width,
+            height: config.height,
+            fit: Fit::from_option(config.fit.as_deref())?,
+            pad: config.pad.as_deref().map(Pad::parse).transpose()?,
+            scale_flags: config.scale_flags.clone(),
+        })
+    }
+
+    /// Scale to `width` when no output size is configured
+    pub fn with_default_width(mut self, width: u32) -> Self {
+        if self.width.is_none() && self.height.is_none() {
+            self.width = Some(width);
+        }
+        self
+    }
+
+    /// Filters for the transform, in order. With `even` set, the output
+    /// width and height are rounded to even numbers, as chroma-subsampled
+    /// pixel formats such as `yuv420p` require.
+    pub fn filters(&self, even: bool) -> Vec<Filter> {
+        let round = |n: u32| if even { n + n % 2 } else { n };
+        let auto = if even { -2 } else { -1 };
+        let flags = self.scale_flags.as_deref().unwrap_or("lanczos");
+        let mut filters = Vec::new();
+        // Whether each output dimension is known to be even (or `even` is off)
+        let (mut even_w, mut even_h) = (!even, !even);
+
+        if let Some(crop) = self.crop {
+            filters.push(match (crop.x, crop.y) {
+                (Some(x), Some(y)) => Filter::crop(crop.width, crop.height).arg(x).arg(y),
+                _ => Filter::crop(crop.width, crop.height),
+            });
+            even_w |= crop.width % 2 == 0;
+            even_h |= crop.height % 2 == 0;
+        }
+
+        match (self.width.map(round), self.height.map(round)) {
+            (None, None) => {}
+            (Some(w), None) => {
+                filters.push(Filter::scale(w, auto).opt("flags", flags));
+                (even_w, even_h) = (true, true);
+            }
+            (None, Some(h)) => {
+                filters.push(Filter::scale(auto, h).opt("flags", flags));
+                (even_w, even_h) = (true, true);
+            }
+            (Some(w), Some(h)) => {
+                match self.fit {
+                    Fit::Stretch => filters.push(Filter::scale(w, h).opt("flags", flags)),
+                    Fit::Contain => {
+                        filters.push(
+                            Filter::scale(w, h)
+                                .opt("force_original_aspect_ratio", "decrease")
+                                .opt("flags", flags),
+                        );
+                        filters.push(Filter::pad(w, h, "(ow-iw)/2", "(oh-ih)/2", PAD_COLOR));
+                    }
+                    Fit::Cover => {
+                        filters.push(
+                            Filter::scale(w, h)
+                                .opt("force_original_aspect_ratio", "increase")
+                                .opt("flags", flags),
+                        );
+                        filters.push(Filter::crop(w, h));
+                    }
+                }
+                (even_w, even_h) = (true, true);
+            }
+        }
+
+        if let Some(pad) = self.pad.filter(|p| *p != Pad::default()) {
+            filters.push(Filter::pad(
+                format!("iw+{}", pad.left + pad.right),
+                format!("ih+{}", pad.top + pad.bottom),
+                pad.left,
+                pad.top,
+                PAD_COLOR,
+            ));
+            even_w &= (pad.left + pad.right) % 2 == 0;
+            even_h &= (pad.top + pad.bottom) % 2 == 0;
+        }
+
+        if even && !(even_w && even_h) {
+            // Drop a trailing row/column rather than resampling the frame
+            filters.push(Filter::crop("trunc(iw/2)*2", "trunc(ih/2)*2"));
+        }
+        filters
+    }
+}
+
+/// Whether `pix_fmt` stores chroma at reduced resolution, so frames must
+/// have even dimensions
+pub fn is_subsampled(pix_fmt: &str) -> bool {
+    ["420p", "422p", "411p", "410p", "nv12", "nv21"]
+        .iter()
+        .any(|layout| pix_fmt.contains(layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(filters: Vec<Filter>) -> Vec<String> {
+        filters.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn no_options_only_rounds_for_subsampled_formats() {
+        let transform = Transform::default();
+        assert!(render(transform.filters(false)).is_empty());
+        assert_eq!(
+            render(transform.filters(true)),
+            ["crop=trunc(iw/2)*2:trunc(ih/2)*2"]
+        );
+        assert!(is_subsampled("yuva420p"));
+        assert!(is_subsampled("yuv422p10le"));
+        assert!(!is_subsampled("yuva444p10le"));
+        assert!(!is_subsampled("rgba"));
+    }
+
+    #[test]
+    fn default_width_keeps_gif_scaling() {
+        let transform = Transform::default().with_default_width(640);
+        assert_eq!(
+            render(transform.filters(false)),
+            ["scale=640:-1:flags=lanczos"]
+        );
+
+        let transform = Transform {
+            height: Some(240),
+            scale_flags: Some("neighbor".into()),
+            ..Default::default()
+        }
+        .with_default_width(640);
+        assert_eq!(
+            render(transform.filters(false)),
+            ["scale=-1:240:flags=neighbor"]
+        );
+    }
+
+    #[test]
+    fn fit_modes_build_scale_chains() {
+        let sized = |fit| Transform {
+            width: Some(1280),
+            height: Some(720),
+            fit,
+            ..Default::default()
+        };
+        assert_eq!(
+            render(sized(Fit::Stretch).filters(true)),
+            ["scale=1280:720:flags=lanczos"]
+        );
+        assert_eq!(
+            render(sized(Fit::Contain).filters(true)),
+            [
+                "scale=1280:720:force_original_aspect_ratio=decrease:flags=lanczos",
+                "pad=1280:720:(ow-iw)/2:(oh-ih)/2:color=black@0"
+            ]
+        );
+        assert_eq!(
+            render(sized(Fit::Cover).filters(false)),
+            [
+                "scale=1280:720:force_original_aspect_ratio=increase:flags=lanczos",
+                "crop=1280:720"
+            ]
+        );
+    }
+
+    #[test]
+    fn odd_sizes_round_up_for_subsampled_formats() {
+        let transform = Transform {
+            width: Some(321),
+            ..Default::default()
+        };
+        assert_eq!(
+            render(transform.filters(true)),
+            ["scale=322:-2:flags=lanczos"]
+        );
+        assert_eq!(
+            render(transform.filters(false)),
+            ["scale=321:-1:flags=lanczos"]
+        );
+
+        let transform = Transform {
+            crop: Some(Crop::parse("101:50").unwrap()),
+            pad: Some(Pad::parse("2").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            render(transform.filters(true)),
+            [
+                "crop=101:50",
+                "pad=iw+4:ih+4:2:2:color=black@0",
+                "crop=trunc(iw/2)*2:trunc(ih/2)*2"
+            ]
+        );
+    }
+
+    #[test]
+    fn crop_runs_before_scale_and_pad_after() {
+        let transform = Transform {
+            crop: Some(Crop::parse("800:600:10:20").unwrap()),
+            width: Some(400),
+            pad: Some(Pad::parse("4:8:12:16").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            render(transform.filters(true)),
+            [
+                "crop=800:600:10:20",
+                "scale=400:-2:flags=lanczos",
+                "pad=iw+24:ih+16:16:4:color=black@0"
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_options() {
+        assert!(Crop::parse("100").is_err());
+        assert!(Crop::parse("0:10").is_err());
+        assert!(Crop::parse("10:10:5").is_err());
+        assert!(Pad::parse("1:2:3").is_err());
+        assert!(Pad::parse("-4").is_err());
+        assert_eq!(Pad::parse("6:3").unwrap().left, 3);
+        assert!(Fit::from_option(Some("fill")).is_err());
+        assert_eq!(Fit::from_option(Some("Cover")).unwrap(), Fit::Cover);
+    }
+}
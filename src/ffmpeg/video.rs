@@ -1,72 +1,95 @@
 use std::path::PathBuf;
 
+use crate::cancel::CancellationToken;
+use crate::error::RenderError;
+use crate::ffmpeg::filter::FilterGraph;
+use crate::ffmpeg::source::FrameSource;
+use crate::observer::{RenderObserver, RenderStage};
 use crate::report::RenderReport;
 use crate::utils;
 
-/// Render a video (webm/mp4) using ffmpeg
+/// Default constant-quality level for VP9 when neither CRF nor bitrate is set
+const VP9_DEFAULT_CRF: u32 = 31;
+
+/// Render a video (webm/webm-vp9/mp4/mov) using ffmpeg
+#[allow(clippy::too_many_arguments)]
 pub fn render_video(
-    input_pattern: &str,
+    source: &FrameSource,
     output: &str,
     fps: u32,
     format: &str,
     bitrate: Option<&str>,
     crf: Option<u32>,
-    fade_filter: Option<&str>,
+    prores_profile: Option<&str>,
+    filters: &FilterGraph,
     verbose_ffmpeg: bool,
-) -> Result<RenderReport, String> {
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<RenderReport, RenderError> {
     let codec = match format {
         "webm" => "libvpx",
+        "webm-vp9" => "libvpx-vp9",
         "mp4" => "libx264",
-        _ => {
-            return Err(format!(
-                "❌ Unsupported format: {}. Use 'webm' or 'mp4'.",
-                format
-            ));
-        }
+        "mov" => "prores_ks",
+        _ => return Err(RenderError::UnsupportedFormat(format.to_string())),
     };
 
-    let pix_fmt = match format {
-        "webm" => "yuva420p",
-        "mp4" => "yuv420p",
-        _ => unreachable!(),
+    let prores = if format == "mov" {
+        Some(prores_profile_settings(prores_profile)?)
+    } else {
+        None
     };
+    let pix_fmt = pixel_format(format, prores_profile)?;
 
-    let mut args: Vec<String> = vec!["-framerate".into(), fps.to_string()];
-
-    if input_pattern.contains('*') {
-        args.push("-pattern_type".into());
-        args.push("glob".into());
-    }
-
-    args.push("-i".into());
-    args.push(input_pattern.to_string());
+    let mut args = source.input_args(fps);
 
     args.extend_from_slice(&[
         "-c:v".into(),
         codec.to_string(),
         "-pix_fmt".into(),
         pix_fmt.to_string(),
-        "-auto-alt-ref".into(),
-        "0".into(),
     ]);
 
-    if let Some(b) = bitrate {
-        args.push("-b:v".into());
-        args.push(b.to_string());
+    if codec.starts_with("libvpx") {
+        // Alt-ref frames are incompatible with alpha in libvpx
+        args.push("-auto-alt-ref".into());
+        args.push("0".into());
     }
 
-    if let Some(c) = crf {
-        args.push("-crf".into());
-        args.push(c.to_string());
-    }
+    if let Some((profile, _)) = prores {
+        // ProRes is intra-only with a fixed data rate per profile, so bitrate/CRF don't apply
+        args.extend_from_slice(&[
+            "-profile:v".into(),
+            profile.to_string(),
+            "-vendor".into(),
+            "apl0".into(),
+        ]);
+    } else if format == "webm-vp9" {
+        // Multi-threaded row encoding with 4 tile columns (log2 = 2)
+        args.extend_from_slice(&[
+            "-row-mt".into(),
+            "1".into(),
+            "-tile-columns".into(),
+            "2".into(),
+        ]);
+        args.extend(vp9_rate_control(bitrate, crf));
+    } else {
+        if let Some(b) = bitrate {
+            args.push("-b:v".into());
+            args.push(b.to_string());
+        }
 
-    if let Some(filter) = fade_filter {
-        if !filter.is_empty() {
-            args.push("-vf".into());
-            args.push(filter.to_string());
+        if let Some(c) = crf {
+            args.push("-crf".into());
+            args.push(c.to_string());
         }
     }
 
+    if !filters.is_empty() {
+        args.push("-filter_complex".into());
+        args.push(filters.to_string());
+    }
+
     args.push("-y".into()); // Overwrite output file if it exists
     args.push(output.to_string());
     if !verbose_ffmpeg {
@@ -74,10 +97,14 @@ pub fn render_video(
         args.push("warning".into());
     }
 
-    let video_stderr = match utils::run_ffmpeg_with_output(&args) {
-        Ok((_, stderr)) => stderr,
-        Err(e) => return Err(format!("❌ Failed to execute ffmpeg: {}", e)),
-    };
+    observer.on_stage(RenderStage::Encoding);
+    let (_, video_stderr) = utils::run_ffmpeg_with_input(
+        &args,
+        source.stdin_feed(),
+        &mut |p| observer.on_frame_progress(p),
+        cancel,
+    )
+    .inspect_err(|e| utils::remove_partial_output(e, output))?;
     let _video_warnings = utils::scan_ffmpeg_stderr(&video_stderr);
 
     Ok(RenderReport {
@@ -86,5 +113,96 @@ pub fn render_video(
         ffmpeg_warnings: _video_warnings,
         preview: false,
         notes: Some("Video render complete.".into()),
+        sequence_issues: Vec::new(),
+        frame_check: None,
     })
 }
+
+/// Pixel format encoded for a video `format`
+pub fn pixel_format(
+    format: &str,
+    prores_profile: Option<&str>,
+) -> Result<&'static str, RenderError> {
+    Ok(match format {
+        "mov" => prores_profile_settings(prores_profile)?.1,
+        "webm" | "webm-vp9" => "yuva420p",
+        _ => "yuv420p",
+    })
+}
+
+/// Whether `pix_fmt` stores more than 8 bits per component, as ProRes does.
+/// Filters feeding such formats must not drop 16-bit frames to 8 bits.
+pub fn is_high_bit_depth(pix_fmt: &str) -> bool {
+    ["p9", "p10", "p12", "p14", "p16", "48", "64"]
+        .iter()
+        .any(|depth| pix_fmt.contains(depth))
+}
+
+/// Map a ProRes profile name to its `prores_ks` profile number and pixel
+/// format. Defaults to 4444, the lowest profile that keeps alpha.
+fn prores_profile_settings(profile: Option<&str>) -> Result<(u8, &'static str), RenderError> {
+    match profile.unwrap_or("4444").to_lowercase().as_str() {
+        "proxy" => Ok((0, "yuv422p10le")),
+        "lt" => Ok((1, "yuv422p10le")),
+        "standard" => Ok((2, "yuv422p10le")),
+        "hq" => Ok((3, "yuv422p10le")),
+        "4444" => Ok((4, "yuva444p10le")),
+        "4444xq" => Ok((5, "yuva444p10le")),
+        other => Err(RenderError::InvalidOption {
+            name: "prores_profile",
+            value: other.to_string(),
+        }),
+    }
+}
+
+/// libvpx-vp9 only runs in constant-quality mode when `-b:v 0` accompanies
+/// `-crf`. CRF takes priority over bitrate, matching the CLI docs.
+fn vp9_rate_control(bitrate: Option<&str>, crf: Option<u32>) -> Vec<String> {
+    match (crf, bitrate) {
+        (Some(c), _) => vec!["-crf".into(), c.to_string(), "-b:v".into(), "0".into()],
+        (None, Some(b)) => vec!["-b:v".into(), b.to_string()],
+        (None, None) => vec![
+            "-crf".into(),
+            VP9_DEFAULT_CRF.to_string(),
+            "-b:v".into(),
+            "0".into(),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_high_bit_depth, pixel_format, prores_profile_settings, vp9_rate_control};
+
+    #[test]
+    fn vp9_crf_uses_constant_quality() {
+        assert_eq!(
+            vp9_rate_control(Some("2M"), Some(24)),
+            ["-crf", "24", "-b:v", "0"]
+        );
+        assert_eq!(vp9_rate_control(Some("2M"), None), ["-b:v", "2M"]);
+        assert_eq!(vp9_rate_control(None, None), ["-crf", "31", "-b:v", "0"]);
+    }
+
+    #[test]
+    fn prores_profiles_map_to_alpha_formats() {
+        assert_eq!(prores_profile_settings(None).unwrap(), (4, "yuva444p10le"));
+        assert_eq!(
+            prores_profile_settings(Some("4444XQ")).unwrap(),
+            (5, "yuva444p10le")
+        );
+        assert_eq!(
+            prores_profile_settings(Some("hq")).unwrap(),
+            (3, "yuv422p10le")
+        );
+        assert!(prores_profile_settings(Some("raw")).is_err());
+    }
+
+    #[test]
+    fn only_prores_is_high_bit_depth() {
+        assert!(is_high_bit_depth(pixel_format("mov", None).unwrap()));
+        assert!(is_high_bit_depth(pixel_format("mov", Some("hq")).unwrap()));
+        assert!(!is_high_bit_depth(pixel_format("webm-vp9", None).unwrap()));
+        assert!(!is_high_bit_depth(pixel_format("mp4", None).unwrap()));
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use crate::cancel::CancellationToken;
+use crate::error::RenderError;
+use crate::ffmpeg::filter::FilterGraph;
+use crate::ffmpeg::source::FrameSource;
+use crate::observer::{RenderObserver, RenderStage};
+use crate::report::RenderReport;
+use crate::utils;
+
+/// Default lossy quality, matching libwebp's own default
+const DEFAULT_QUALITY: u32 = 75;
+
+/// Pixel format encoded for WebP output. Lossless keeps full RGBA; lossy
+/// stores alpha as a separate plane.
+pub fn pixel_format(lossless: bool) -> &'static str {
+    if lossless {
+        "bgra"
+    } else {
+        "yuva420p"
+    }
+}
+
+/// Render an animated WebP with full alpha using libwebp_anim
+#[allow(clippy::too_many_arguments)]
+pub fn render_webp(
+    source: &FrameSource,
+    output: &str,
+    fps: u32,
+    lossless: bool,
+    quality: Option<u32>,
+    loop_count: Option<u32>,
+    filters: &FilterGraph,
+    verbose_ffmpeg: bool,
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<RenderReport, RenderError> {
+    let quality = quality.unwrap_or(DEFAULT_QUALITY);
+    if quality > 100 {
+        return Err(RenderError::InvalidOption {
+            name: "quality",
+            value: quality.to_string(),
+        });
+    }
+
+    let mut args = source.input_args(fps);
+
+    if !filters.is_empty() {
+        args.push("-filter_complex".into());
+        args.push(filters.to_string());
+    }
+
+    let pix_fmt = pixel_format(lossless);
+    args.extend_from_slice(&[
+        "-c:v".into(),
+        "libwebp_anim".into(),
+        "-lossless".into(),
+        if lossless { "1" } else { "0" }.into(),
+        "-q:v".into(),
+        quality.to_string(),
+        "-pix_fmt".into(),
+        pix_fmt.into(),
+        "-loop".into(),
+        loop_count.unwrap_or(0).to_string(),
+    ]);
+
+    args.push("-y".into());
+    args.push(output.into());
+    if !verbose_ffmpeg {
+        args.push("-loglevel".into());
+        args.push("warning".into());
+    }
+
+    observer.on_stage(RenderStage::Encoding);
+    let (_, webp_stderr) = utils::run_ffmpeg_with_input(
+        &args,
+        source.stdin_feed(),
+        &mut |p| observer.on_frame_progress(p),
+        cancel,
+    )
+    .inspect_err(|e| utils::remove_partial_output(e, output))?;
+    let webp_warnings = utils::scan_ffmpeg_stderr(&webp_stderr);
+
+    let mode = if lossless {
+        "lossless".to_string()
+    } else {
+        format!("lossy, quality {}", quality)
+    };
+    Ok(RenderReport {
+        output_path: PathBuf::from(output),
+        frames_rendered: None,
+        ffmpeg_warnings: webp_warnings,
+        preview: false,
+        notes: Some(format!("Animated WebP export ({})", mode)),
+        sequence_issues: Vec::new(),
+        frame_check: None,
+    })
+}
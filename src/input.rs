@@ -1,23 +1,579 @@
 use glob::glob;
+use std::cmp::Ordering;
+use std::fmt;
 use std::path::{Path, PathBuf};
 
+use crate::config::RenderConfig;
+use crate::error::RenderError;
+
 /// Collect files from `input_folder` matching the optional pattern.
 /// Defaults to `*.png` when no pattern is provided.
+///
+/// The pattern is either a glob (`*.png`) or a printf-style sequence
+/// (`shot_%05d.png`). Files are returned in natural order, so `frame_2.png`
+/// comes before `frame_10.png` even when frame numbers are not zero-padded.
 pub fn collect_input_frames(
     input_folder: &Path,
     file_pattern: Option<String>,
-) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
-    let pattern = file_pattern.unwrap_or_else(|| "*.png".to_string());
-    let glob_path = format!("{}/{}", input_folder.display(), pattern);
-    let mut frames: Vec<PathBuf> = glob(&glob_path)?.filter_map(Result::ok).collect();
-    frames.sort();
+) -> Result<Vec<PathBuf>, RenderError> {
+    let pattern = FramePattern::new(file_pattern.as_deref().unwrap_or("*.png"))?;
+    let glob_path = format!("{}/{}", input_folder.display(), pattern.glob());
+    let mut frames: Vec<PathBuf> = glob(&glob_path)?
+        .filter_map(Result::ok)
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| pattern.matches(n))
+        })
+        .collect();
+    frames.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
     Ok(frames)
 }
 
+/// Start and end of the first `%d` / `%0Nd` field in `pattern`, with the
+/// padding digits (`"5"` for `%05d`, empty for `%d`)
+fn printf_field(pattern: &str) -> Option<(usize, usize, &str)> {
+    pattern.match_indices('%').find_map(|(start, _)| {
+        let spec = &pattern[start + 1..];
+        let digits = match spec.strip_prefix('0') {
+            Some(padded) => {
+                let len = padded.bytes().take_while(u8::is_ascii_digit).count();
+                if len == 0 {
+                    return None;
+                }
+                &padded[..len]
+            }
+            None => "",
+        };
+        let field_len = if digits.is_empty() {
+            0
+        } else {
+            1 + digits.len()
+        };
+        spec[field_len..]
+            .starts_with('d')
+            .then(|| (start, start + 2 + field_len, digits))
+    })
+}
+
+/// A file name pattern for input frames: a glob such as `*.png`, or a
+/// printf-style sequence such as `shot_%05d.png`
+#[derive(Debug, Clone)]
+pub enum FramePattern {
+    Glob(glob::Pattern),
+    Sequence {
+        prefix: String,
+        /// Minimum digit count for zero-padded sequences (`%05d` → 5)
+        width: Option<usize>,
+        suffix: String,
+    },
+}
+
+impl FramePattern {
+    /// Parse `pattern`. Only `%d` and `%0Nd` make a sequence; any other `%`
+    /// is a literal character of a glob (`100%_*.png`).
+    pub fn new(pattern: &str) -> Result<Self, RenderError> {
+        let Some((start, end, digits)) = printf_field(pattern) else {
+            return Ok(FramePattern::Glob(glob::Pattern::new(pattern)?));
+        };
+        let invalid = || RenderError::InvalidOption {
+            name: "file_pattern",
+            value: pattern.to_string(),
+        };
+        let width = match digits {
+            "" => None,
+            d => Some(d.parse().map_err(|_| invalid())?),
+        };
+        let suffix = &pattern[end..];
+        if printf_field(suffix).is_some() {
+            return Err(invalid());
+        }
+        Ok(FramePattern::Sequence {
+            prefix: pattern[..start].to_string(),
+            width,
+            suffix: suffix.to_string(),
+        })
+    }
+
+    /// Glob used to list candidate files on disk
+    pub fn glob(&self) -> String {
+        match self {
+            FramePattern::Glob(pattern) => pattern.as_str().to_string(),
+            FramePattern::Sequence { prefix, suffix, .. } => format!(
+                "{}[0-9]*{}",
+                glob::Pattern::escape(prefix),
+                glob::Pattern::escape(suffix)
+            ),
+        }
+    }
+
+    /// Whether `file_name` belongs to this pattern
+    pub fn matches(&self, file_name: &str) -> bool {
+        match self {
+            FramePattern::Glob(pattern) => pattern.matches(file_name),
+            FramePattern::Sequence {
+                prefix,
+                width,
+                suffix,
+            } => {
+                let Some(number) = file_name
+                    .strip_prefix(prefix.as_str())
+                    .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+                else {
+                    return false;
+                };
+                if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+                    return false;
+                }
+                // Mirror printf: padded to `width`, never extra leading zeros
+                let padded_len = width.unwrap_or(1);
+                number.len() == padded_len
+                    || (number.len() > padded_len && !number.starts_with('0'))
+            }
+        }
+    }
+}
+
+/// Which frame numbers to render: `start_frame..=end_frame`, every
+/// `frame_step`th frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameRange {
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+    pub step: u64,
+}
+
+impl Default for FrameRange {
+    fn default() -> Self {
+        Self {
+            start: None,
+            end: None,
+            step: 1,
+        }
+    }
+}
+
+impl FrameRange {
+    pub fn from_config(config: &RenderConfig) -> Result<Self, RenderError> {
+        if config.frame_step == Some(0) {
+            return Err(RenderError::InvalidOption {
+                name: "frame_step",
+                value: "0".into(),
+            });
+        }
+        if let (Some(start), Some(end)) = (config.start_frame, config.end_frame) {
+            if end < start {
+                return Err(RenderError::InvalidOption {
+                    name: "end_frame",
+                    value: end.to_string(),
+                });
+            }
+        }
+        Ok(Self {
+            start: config.start_frame,
+            end: config.end_frame,
+            step: config.frame_step.unwrap_or(1) as u64,
+        })
+    }
+
+    pub fn is_full(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Keep the items whose frame number falls in the range. Steps count from
+    /// `start`, or from the first numbered item. Unnumbered items are only
+    /// kept when the range selects everything.
+    pub fn select<T>(&self, items: Vec<T>, number: impl Fn(&T) -> Option<u64>) -> Vec<T> {
+        if self.is_full() {
+            return items;
+        }
+        let base = self
+            .start
+            .or_else(|| items.iter().filter_map(&number).min())
+            .unwrap_or(0);
+        items
+            .into_iter()
+            .filter(|item| {
+                number(item).is_some_and(|n| {
+                    n >= base
+                        && self.end.is_none_or(|end| n <= end)
+                        && (n - base).is_multiple_of(self.step)
+                })
+            })
+            .collect()
+    }
+}
+
+/// An image format accepted as input frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+    Tiff,
+    Exr,
+    Tga,
+}
+
+impl ImageFormat {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "jpg" | "jpeg" => Some(ImageFormat::Jpeg),
+            "webp" => Some(ImageFormat::WebP),
+            "tif" | "tiff" => Some(ImageFormat::Tiff),
+            "exr" => Some(ImageFormat::Exr),
+            "tga" => Some(ImageFormat::Tga),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+
+    /// Parse the `input_extensions` option. Defaults to PNG only.
+    pub fn from_config(extensions: Option<&[String]>) -> Result<Vec<Self>, RenderError> {
+        let Some(extensions) = extensions else {
+            return Ok(vec![ImageFormat::Png]);
+        };
+        let mut formats = Vec::new();
+        for ext in extensions {
+            let format = Self::from_extension(ext.trim_start_matches('.')).ok_or_else(|| {
+                RenderError::InvalidOption {
+                    name: "input_extensions",
+                    value: ext.clone(),
+                }
+            })?;
+            if !formats.contains(&format) {
+                formats.push(format);
+            }
+        }
+        Ok(formats)
+    }
+
+    /// Canonical file extension, used when staging frames on disk
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::WebP => "webp",
+            ImageFormat::Tiff => "tiff",
+            ImageFormat::Exr => "exr",
+            ImageFormat::Tga => "tga",
+        }
+    }
+
+    /// The ffmpeg demuxer that splits a stream of these images on stdin.
+    /// TGA has no pipe parser, so TGA frames must be read from numbered files.
+    pub fn pipe_demuxer(self) -> Option<&'static str> {
+        match self {
+            ImageFormat::Png => Some("png_pipe"),
+            ImageFormat::Jpeg => Some("jpeg_pipe"),
+            ImageFormat::WebP => Some("webp_pipe"),
+            ImageFormat::Tiff => Some("tiff_pipe"),
+            ImageFormat::Exr => Some("exr_pipe"),
+            ImageFormat::Tga => None,
+        }
+    }
+
+    /// ffmpeg decoder options placed before `-i`. EXR frames hold linear
+    /// light, so they are converted to the sRGB transfer curve on decode;
+    /// otherwise they would render far too dark.
+    pub fn decoder_args(self) -> &'static [&'static str] {
+        match self {
+            ImageFormat::Exr => &["-apply_trc", "iec61966_2_1"],
+            _ => &[],
+        }
+    }
+
+    /// Default `file_pattern` for a set of accepted formats
+    pub fn default_pattern(formats: &[Self]) -> String {
+        match formats {
+            [format] if *format == ImageFormat::Png => "*.png".into(),
+            _ => "*".into(),
+        }
+    }
+
+    /// The single format shared by every frame, if any frames are given.
+    /// ffmpeg decodes a piped sequence with one decoder, so mixed formats are
+    /// rejected.
+    pub fn of_sequence<P: AsRef<Path>>(
+        frames: impl IntoIterator<Item = P>,
+    ) -> Result<Option<Self>, RenderError> {
+        let mut first: Option<(Self, String)> = None;
+        for frame in frames {
+            let frame = frame.as_ref();
+            let Some(format) = Self::from_path(frame) else {
+                continue;
+            };
+            match &first {
+                None => first = Some((format, frame.display().to_string())),
+                Some((f, name)) if *f != format => {
+                    return Err(RenderError::MixedFrameFormats {
+                        first: name.clone(),
+                        second: frame.display().to_string(),
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(first.map(|(format, _)| format))
+    }
+}
+
+/// Compare two names, treating runs of digits as numbers
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        match (a.chars().next(), b.chars().next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (num_a, rest_a) = split_digits(a);
+                let (num_b, rest_b) = split_digits(b);
+                let trimmed_a = num_a.trim_start_matches('0');
+                let trimmed_b = num_b.trim_start_matches('0');
+                let ord = trimmed_a
+                    .len()
+                    .cmp(&trimmed_b.len())
+                    .then_with(|| trimmed_a.cmp(trimmed_b))
+                    // Equal values: fewer leading zeros first, for a stable order
+                    .then_with(|| num_a.len().cmp(&num_b.len()));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                a = rest_a;
+                b = rest_b;
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a = &a[x.len_utf8()..];
+                b = &b[y.len_utf8()..];
+            }
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+/// Where the frame number sits in the file names of one sequence.
+///
+/// Names often carry other digits besides the frame counter (`frame_0001_v2`,
+/// `img_0001@2x`, `shot_v2_0120`), so the counter is the run of digits that
+/// changes from file to file rather than simply the last one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameNumbering {
+    /// The `%0Nd` field of a printf-style pattern, between `prefix` and `suffix`
+    Field { prefix: String, suffix: String },
+    /// A run of digits in the file stem: its index and how many runs the
+    /// names have, or the last run when unknown
+    DigitRun(Option<(usize, usize)>),
+}
+
+impl Default for FrameNumbering {
+    fn default() -> Self {
+        FrameNumbering::DigitRun(None)
+    }
+}
+
+impl FrameNumbering {
+    /// Numbering for frames collected with `pattern`: the `%0Nd` field of a
+    /// sequence pattern, otherwise detected from the file names
+    pub fn for_pattern<P: AsRef<Path>>(
+        pattern: &FramePattern,
+        frames: impl IntoIterator<Item = P>,
+    ) -> Self {
+        match pattern {
+            FramePattern::Sequence { prefix, suffix, .. } => FrameNumbering::Field {
+                prefix: prefix.clone(),
+                suffix: suffix.clone(),
+            },
+            FramePattern::Glob(_) => Self::detect(frames),
+        }
+    }
+
+    /// Find the counting digit run of a sequence: the last run whose value
+    /// differs between files, or for a single file the longest run
+    pub fn detect<P: AsRef<Path>>(frames: impl IntoIterator<Item = P>) -> Self {
+        let mut first: Option<Vec<String>> = None;
+        let mut varies = Vec::new();
+        for frame in frames {
+            let Some(stem) = stem_of(frame.as_ref()) else {
+                continue;
+            };
+            let runs = digit_runs(stem);
+            match &first {
+                None => {
+                    varies = vec![false; runs.len()];
+                    first = Some(runs.into_iter().map(String::from).collect());
+                }
+                // Names shaped differently say nothing about which run counts
+                Some(first) if first.len() == runs.len() => {
+                    for (i, (a, b)) in first.iter().zip(&runs).enumerate() {
+                        varies[i] |= a.parse::<u64>().ok() != b.parse().ok();
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+        let Some(first) = first else {
+            return Self::default();
+        };
+        let index = varies.iter().rposition(|&v| v).or_else(|| {
+            // Nothing changes (a single frame): the counter is usually the
+            // zero-padded run, so prefer the longest, then the last
+            (0..first.len()).max_by_key(|&i| first[i].len())
+        });
+        FrameNumbering::DigitRun(index.map(|i| (i, first.len())))
+    }
+
+    /// The frame number of `path`. Digit runs fall back to the last run when
+    /// the name does not have the sequence's shape.
+    pub fn number(&self, path: &Path) -> Option<u64> {
+        let run = match self {
+            FrameNumbering::Field { prefix, suffix } => path
+                .file_name()?
+                .to_str()?
+                .strip_prefix(prefix.as_str())?
+                .strip_suffix(suffix.as_str())?,
+            FrameNumbering::DigitRun(run) => {
+                let runs = digit_runs(stem_of(path)?);
+                match run {
+                    Some((index, count)) if runs.len() == *count => runs[*index],
+                    _ => *runs.last()?,
+                }
+            }
+        };
+        run.parse().ok()
+    }
+}
+
+fn stem_of(path: &Path) -> Option<&str> {
+    path.file_stem()?.to_str()
+}
+
+/// The runs of ASCII digits in `s`, in order
+fn digit_runs(s: &str) -> Vec<&str> {
+    s.split(|c: char| !c.is_ascii_digit())
+        .filter(|run| !run.is_empty())
+        .collect()
+}
+
+/// What to do when frame numbers have gaps or duplicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GapPolicy {
+    /// Abort the render with [`RenderError::FrameSequenceGaps`]
+    Fail,
+    /// Repeat the previous frame for each missing number
+    Hold,
+    /// Render the frames that exist, back to back
+    #[default]
+    Skip,
+}
+
+impl GapPolicy {
+    /// Parse the `on_frame_gaps` option (`fail`, `hold` or `skip`)
+    pub fn from_option(value: Option<&str>) -> Result<Self, RenderError> {
+        match value {
+            None | Some("skip") => Ok(GapPolicy::Skip),
+            Some("hold") => Ok(GapPolicy::Hold),
+            Some("fail") => Ok(GapPolicy::Fail),
+            Some(other) => Err(RenderError::InvalidOption {
+                name: "on_frame_gaps",
+                value: other.to_string(),
+            }),
+        }
+    }
+}
+
+/// A problem found in the numbering of a frame sequence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SequenceIssue {
+    /// Frame numbers `first..=last` are absent
+    Missing { first: u64, last: u64 },
+    /// More than one file carries this frame number
+    Duplicate(u64),
+}
+
+impl fmt::Display for SequenceIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SequenceIssue::Missing { first, last } if first == last => {
+                write!(f, "frame {} missing", first)
+            }
+            SequenceIssue::Missing { first, last } => {
+                write!(f, "frames {}\u{2013}{} missing", first, last)
+            }
+            SequenceIssue::Duplicate(n) => write!(f, "frame {} appears more than once", n),
+        }
+    }
+}
+
+/// Check the numbering of frames (in render order) and decide which of them
+/// to feed ffmpeg. Consecutive frames are expected `step` numbers apart.
+///
+/// Returns the indices into `numbers` to render, in order, along with the
+/// issues found. Frames without a number are rendered as-is and never
+/// reported. Duplicate numbers are reported but every copy is still
+/// rendered; [`GapPolicy::Fail`] rejects any issue.
+pub fn plan_sequence(
+    numbers: &[Option<u64>],
+    step: u64,
+    policy: GapPolicy,
+) -> Result<(Vec<usize>, Vec<SequenceIssue>), RenderError> {
+    let mut order = Vec::with_capacity(numbers.len());
+    let mut issues = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut previous: Option<(usize, u64)> = None;
+
+    for (index, number) in numbers.iter().enumerate() {
+        let Some(n) = *number else {
+            order.push(index);
+            continue;
+        };
+        if !seen.insert(n) && !issues.contains(&SequenceIssue::Duplicate(n)) {
+            issues.push(SequenceIssue::Duplicate(n));
+        }
+        if let Some((prev_index, prev)) = previous {
+            if n > prev + step {
+                issues.push(SequenceIssue::Missing {
+                    first: prev + step,
+                    last: n - step,
+                });
+                if policy == GapPolicy::Hold {
+                    let held = (n - prev) / step - 1;
+                    order.extend(std::iter::repeat_n(prev_index, held as usize));
+                }
+            }
+        }
+        previous = Some((index, n));
+        order.push(index);
+    }
+
+    if policy == GapPolicy::Fail && !issues.is_empty() {
+        return Err(RenderError::FrameSequenceGaps(
+            issues.iter().map(ToString::to_string).collect(),
+        ));
+    }
+    Ok((order, issues))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::collect_input_frames;
+    use super::{
+        collect_input_frames, plan_sequence, FrameNumbering, FramePattern, FrameRange, GapPolicy,
+        ImageFormat, SequenceIssue,
+    };
+    use crate::error::RenderError;
     use std::fs::File;
+    use std::path::Path;
     use tempfile::tempdir;
 
     #[test]
@@ -40,4 +596,190 @@ mod tests {
         assert!(frames[0].ends_with("scene1_001.png"));
         Ok(())
     }
+
+    #[test]
+    fn sorts_unpadded_numbers_naturally() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        for name in ["frame_10.png", "frame_2.png", "frame_1.png"] {
+            File::create(dir.path().join(name))?;
+        }
+        let frames = collect_input_frames(dir.path(), None)?;
+        let names: Vec<_> = frames
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["frame_1.png", "frame_2.png", "frame_10.png"]);
+        Ok(())
+    }
+
+    #[test]
+    fn numbers_frames_by_the_digit_run_that_changes() {
+        let numbering = FrameNumbering::detect(["shot_v2_0119.png", "shot_v2_0120.png"]);
+        assert_eq!(numbering.number(Path::new("shot_v2_0120.png")), Some(120));
+
+        let numbering = FrameNumbering::default();
+        assert_eq!(numbering.number(Path::new("frames/7.png")), Some(7));
+        assert_eq!(numbering.number(Path::new("cover.png")), None);
+
+        // A lone frame is numbered by its longest run
+        let numbering = FrameNumbering::detect(["img_0001@2x.png"]);
+        assert_eq!(numbering.number(Path::new("img_0001@2x.png")), Some(1));
+    }
+
+    #[test]
+    fn digits_after_the_frame_number_do_not_collapse_the_sequence(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for suffix in ["_v2", "@2x"] {
+            let dir = tempdir()?;
+            for n in 1..=5 {
+                File::create(dir.path().join(format!("frame_{:04}{}.png", n, suffix)))?;
+            }
+            let frames = collect_input_frames(dir.path(), None)?;
+            let numbering = FrameNumbering::detect(&frames);
+            let numbers: Vec<_> = frames.iter().map(|f| numbering.number(f)).collect();
+            assert_eq!(numbers, [1, 2, 3, 4, 5].map(Some));
+
+            let (order, issues) = plan_sequence(&numbers, 1, GapPolicy::Skip)?;
+            assert_eq!(order.len(), 5);
+            assert!(issues.is_empty());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reports_gaps_and_duplicates() {
+        let numbers = [Some(1), Some(2), Some(2), Some(5), Some(6)];
+        let (order, issues) = plan_sequence(&numbers, 1, GapPolicy::Skip).unwrap();
+        // Duplicates are reported, never dropped
+        assert_eq!(order, [0, 1, 2, 3, 4]);
+        assert_eq!(
+            issues,
+            [
+                SequenceIssue::Duplicate(2),
+                SequenceIssue::Missing { first: 3, last: 4 }
+            ]
+        );
+        assert_eq!(issues[1].to_string(), "frames 3\u{2013}4 missing");
+    }
+
+    #[test]
+    fn hold_repeats_previous_frame() {
+        let numbers = [Some(1), Some(4), None];
+        let (order, _) = plan_sequence(&numbers, 1, GapPolicy::Hold).unwrap();
+        assert_eq!(order, [0, 0, 0, 1, 2]);
+    }
+
+    #[test]
+    fn fail_policy_rejects_gaps() {
+        let err = plan_sequence(&[Some(1), Some(3)], 1, GapPolicy::Fail).unwrap_err();
+        assert!(
+            matches!(err, RenderError::FrameSequenceGaps(ref issues) if issues == &["frame 2 missing"])
+        );
+        assert!(plan_sequence(&[Some(1), Some(2)], 1, GapPolicy::Fail).is_ok());
+    }
+
+    #[test]
+    fn collects_printf_sequences() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        for name in [
+            "shot_01001.png",
+            "shot_01002.png",
+            "shot_1003.png",
+            "shot_x.png",
+        ] {
+            File::create(dir.path().join(name))?;
+        }
+        let frames = collect_input_frames(dir.path(), Some("shot_%05d.png".into()))?;
+        assert_eq!(frames.len(), 2);
+        assert!(frames[1].ends_with("shot_01002.png"));
+
+        let unpadded = FramePattern::new("shot_%d.png")?;
+        assert!(unpadded.matches("shot_1003.png"));
+        assert!(!unpadded.matches("shot_01003.png"));
+        assert!(FramePattern::new("shot_%05d_%d.png").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn literal_percent_signs_stay_in_globs() -> Result<(), Box<dyn std::error::Error>> {
+        for literal in ["100%_*.png", "shot_%s.png", "50%0_*.png"] {
+            assert!(matches!(FramePattern::new(literal)?, FramePattern::Glob(_)));
+        }
+
+        let dir = tempdir()?;
+        for name in ["100%_0001.png", "100%_0002.png", "50_0001.png"] {
+            File::create(dir.path().join(name))?;
+        }
+        let frames = collect_input_frames(dir.path(), Some("100%_*.png".into()))?;
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].ends_with("100%_0001.png"));
+
+        // A literal percent sign may still sit next to a sequence field
+        let mixed = FramePattern::new("100%_%04d.png")?;
+        assert!(mixed.matches("100%_0001.png"));
+        Ok(())
+    }
+
+    #[test]
+    fn sequence_patterns_number_frames_by_their_field() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        for n in 1000..=1003 {
+            File::create(dir.path().join(format!("shot_{:05}_v2.png", n)))?;
+        }
+        let pattern = FramePattern::new("shot_%05d_v2.png")?;
+        let frames = collect_input_frames(dir.path(), Some("shot_%05d_v2.png".into()))?;
+        let numbering = FrameNumbering::for_pattern(&pattern, &frames);
+        let range = FrameRange {
+            start: Some(1001),
+            end: Some(1002),
+            step: 1,
+        };
+        let selected = range.select(frames, |f| numbering.number(f));
+        assert_eq!(selected.len(), 2);
+        assert!(selected[0].ends_with("shot_01001_v2.png"));
+        assert!(selected[1].ends_with("shot_01002_v2.png"));
+        Ok(())
+    }
+
+    #[test]
+    fn frame_range_selects_numbers_with_step() {
+        let range = FrameRange {
+            start: Some(1001),
+            end: Some(1006),
+            step: 2,
+        };
+        let numbers: Vec<u64> = (1000..=1008).collect();
+        assert_eq!(range.select(numbers, |n| Some(*n)), [1001, 1003, 1005]);
+
+        let (order, issues) =
+            plan_sequence(&[Some(1001), Some(1003), Some(1007)], 2, GapPolicy::Hold).unwrap();
+        assert_eq!(order, [0, 1, 1, 2]);
+        assert_eq!(
+            issues,
+            [SequenceIssue::Missing {
+                first: 1005,
+                last: 1005
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_input_extensions_and_rejects_mixed_sequences() {
+        let exts = ["JPG".to_string(), ".tif".into(), "jpeg".into()];
+        assert_eq!(
+            ImageFormat::from_config(Some(&exts)).unwrap(),
+            [ImageFormat::Jpeg, ImageFormat::Tiff]
+        );
+        assert!(ImageFormat::from_config(Some(&["bmp".to_string()])).is_err());
+        assert_eq!(ImageFormat::from_config(None).unwrap(), [ImageFormat::Png]);
+
+        assert_eq!(
+            ImageFormat::of_sequence(["a_1.exr", "a_2.exr"]).unwrap(),
+            Some(ImageFormat::Exr)
+        );
+        assert!(matches!(
+            ImageFormat::of_sequence(["a_1.png", "a_2.jpg"]),
+            Err(RenderError::MixedFrameFormats { .. })
+        ));
+    }
 }
@@ -1,43 +1,140 @@
+pub mod cancel;
 pub mod config;
+pub mod error;
 pub mod ffmpeg;
 pub mod input;
+pub mod observer;
+pub mod progress;
 pub mod report;
 pub mod utils;
+pub mod validate;
 
+pub use cancel::CancellationToken;
 pub use config::RenderConfig;
+pub use error::RenderError;
+pub use ffmpeg::source::MemoryFrames;
+pub use observer::{NoopObserver, RenderObserver, RenderStage};
+pub use progress::FfmpegProgress;
 pub use report::RenderReport;
 
-use indicatif::{ProgressBar, ProgressStyle};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::Duration;
 
 /// Load configuration from file then render
-pub fn render_from_config(config_path: &str) -> Result<RenderReport, String> {
+pub fn render_from_config(config_path: &str) -> Result<RenderReport, RenderError> {
     let args = RenderConfig::from_file(config_path)?;
     render(args)
 }
 
 /// Orchestrate rendering from a parsed configuration
-pub fn render(args: RenderConfig) -> Result<RenderReport, String> {
-    if args.verbose {
-        let version = env!("CARGO_PKG_VERSION");
-        eprintln!("🪼 Aether Renderer v{version} starting...");
-    }
-    // Validate output path
-    if args.output.is_empty() {
-        return Err("❌ Output path cannot be empty.".into());
+pub fn render(args: RenderConfig) -> Result<RenderReport, RenderError> {
+    render_with_observer(args, &mut NoopObserver)
+}
+
+/// Render like [`render`], calling `on_progress` as ffmpeg encodes frames.
+///
+/// Updates carry the total input frame count, so callers can drive a
+/// determinate progress bar.
+pub fn render_with_progress<F>(
+    args: RenderConfig,
+    mut on_progress: F,
+) -> Result<RenderReport, RenderError>
+where
+    F: FnMut(&FfmpegProgress),
+{
+    render_with_observer(args, &mut on_progress)
+}
+
+/// Render like [`render`], reporting stages, progress, warnings and the final
+/// result to `observer`.
+pub fn render_with_observer(
+    args: RenderConfig,
+    observer: &mut dyn RenderObserver,
+) -> Result<RenderReport, RenderError> {
+    render_cancellable(args, observer, &CancellationToken::new())
+}
+
+/// Render like [`render_with_observer`], stopping early once `cancel` is
+/// triggered.
+///
+/// A cancelled render kills ffmpeg, removes the partial output and any GIF
+/// palette, cleans up extracted ZIP frames and returns
+/// [`RenderError::Cancelled`].
+pub fn render_cancellable(
+    args: RenderConfig,
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<RenderReport, RenderError> {
+    let result = run_render(args, observer, cancel);
+    observer.on_finished(result.as_ref());
+    result
+}
+
+/// Render frames produced in memory (encoded PNGs or raw RGBA buffers),
+/// streaming them to ffmpeg without writing intermediate files.
+///
+/// `args.input` and the frame selection options are ignored.
+pub fn render_frames(
+    args: RenderConfig,
+    frames: MemoryFrames,
+) -> Result<RenderReport, RenderError> {
+    render_frames_cancellable(args, frames, &mut NoopObserver, &CancellationToken::new())
+}
+
+/// Render in-memory frames like [`render_frames`], reporting to `observer`
+/// and stopping early once `cancel` is triggered
+pub fn render_frames_cancellable(
+    args: RenderConfig,
+    frames: MemoryFrames,
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<RenderReport, RenderError> {
+    let result = run_render_frames(args, frames, observer, cancel);
+    observer.on_finished(result.as_ref());
+    result
+}
+
+fn run_render_frames(
+    args: RenderConfig,
+    frames: MemoryFrames,
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<RenderReport, RenderError> {
+    start_render(&args)?;
+    check_ffmpeg(&args, observer, cancel)?;
+
+    // GIF palettes need a second pass, and the fade-out needs the length
+    let frames = if args.format == "gif" || (args.fade_out > 0.0 && frames.count().is_none()) {
+        frames.buffered()
+    } else {
+        frames
+    };
+    if frames.is_empty() {
+        return Err(RenderError::NoMemoryFrames);
     }
+    let frame_count = frames.count();
+    let source = ffmpeg::source::FrameSource::Memory(frames);
+    let frame_check = if args.validate_frames.unwrap_or(true) {
+        observer.on_stage(RenderStage::ValidatingFrames);
+        validate::validate_source(&source, args.decode_frames, cancel)?
+    } else {
+        None
+    };
+    encode(&args, &source, frame_count, frame_check, observer, cancel)
+}
 
-    match args.format.as_str() {
-        "webm" | "mp4" | "gif" => Ok::<(), String>(()),
-        _ => Err("Unsupported format".into()),
-    }?;
+fn run_render(
+    args: RenderConfig,
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<RenderReport, RenderError> {
+    start_render(&args)?;
 
     // Is this a preview render?
     if args.is_preview() {
         if args.open {
-            eprintln!("⚠️ '--open' is only supported for full render. Ignoring for preview.");
+            observer
+                .on_warning("'--open' is only supported for full render. Ignoring for preview.");
         }
         let mut out_path = PathBuf::from(&args.output);
         if out_path.extension().is_some() {
@@ -45,239 +142,624 @@ pub fn render(args: RenderConfig) -> Result<RenderReport, String> {
         } else {
             out_path = out_path.with_extension("png");
         }
-        preview_frame(
+        observer.on_stage(RenderStage::ExtractingPreview);
+        let formats = input::ImageFormat::from_config(args.input_extensions.as_deref())?;
+        let preview_path = preview_frame(
             &args.input,
             args.file_pattern.clone(),
+            args.zip_subfolder.as_deref(),
+            &formats,
             args.preview_frame_limit(),
             &out_path,
-            args.verbose,
         )?;
+        observer.on_info(
+            RenderStage::ExtractingPreview,
+            &format!("Preview saved to: {}", preview_path),
+        );
         return Ok(RenderReport {
-            output_path: PathBuf::from(out_path.to_string_lossy().into_owned()),
+            output_path: PathBuf::from(preview_path),
             frames_rendered: Some(1),
             ffmpeg_warnings: Vec::new(),
             preview: true,
             notes: Some("Preview complete.".into()),
+            sequence_issues: Vec::new(),
+            frame_check: None,
         });
     }
 
-    // Check for ffmpeg availability upfront
-    if args.verbose_ffmpeg {
-        println!("🔍 Checking for ffmpeg...");
+    check_ffmpeg(&args, observer, cancel)?;
+
+    if !args.input.exists() {
+        return Err(RenderError::InputNotFound(args.input.clone()));
     }
-    match {
-        let mut cmd = Command::new("ffmpeg");
-        cmd.arg("-version");
 
-        if !args.verbose_ffmpeg {
-            cmd.stdout(std::process::Stdio::null());
-            cmd.stderr(std::process::Stdio::null());
-        }
+    cancel.check()?;
+    let input_path = &args.input;
+    let archive = utils::ArchiveKind::of_path(input_path);
+    if archive.is_none() && input_path.is_file() {
+        observer.on_stage(RenderStage::ProbingVideo);
+        let info = ffmpeg::probe::probe_video(input_path)?
+            .ok_or_else(|| RenderError::UnrecognizedInput(input_path.clone()))?;
+        // Re-encode an existing video: fades, format and quality options apply
+        let frame_count = info.frame_count;
+        let source = ffmpeg::source::FrameSource::Video {
+            path: input_path.clone(),
+            info,
+        };
+        return encode(&args, &source, frame_count, None, observer, cancel);
+    }
 
-        cmd.status()
-    } {
-        Ok(s) if s.success() => {}
-        Ok(_) => {
-            return Err("❌ ffmpeg failed to run correctly.".into());
-        }
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                return Err(
-                    "❌ ffmpeg not found. Please install ffmpeg and ensure it is in your PATH."
-                        .into(),
-                );
-            } else {
-                return Err(format!("❌ Failed to execute ffmpeg: {}", e));
+    let formats = input::ImageFormat::from_config(args.input_extensions.as_deref())?;
+    let pattern = args
+        .file_pattern
+        .clone()
+        .unwrap_or_else(|| input::ImageFormat::default_pattern(&formats));
+    let gap_policy = input::GapPolicy::from_option(args.on_frame_gaps.as_deref())?;
+    let range = input::FrameRange::from_config(&args)?;
+    // Tar archives have no random access, so their frames are extracted here
+    let mut _extracted_frames = None;
+    let (source, frame_count, sequence_issues) = if let Some(kind) = archive {
+        observer.on_stage(RenderStage::ReadingArchive);
+        let entries = utils::list_archive_frames(
+            input_path,
+            &pattern,
+            args.zip_subfolder.as_deref(),
+            &formats,
+        )?;
+        let numbering = input::FrameNumbering::for_pattern(
+            &input::FramePattern::new(&pattern)?,
+            entries.iter().map(|(_, name)| name),
+        );
+        let entries = range.select(entries, |(_, name)| numbering.number(Path::new(name)));
+        let numbers: Vec<_> = entries
+            .iter()
+            .map(|(_, name)| numbering.number(Path::new(name)))
+            .collect();
+        let (order, issues) = input::plan_sequence(&numbers, range.step, gap_policy)?;
+        let format = input::ImageFormat::of_sequence(entries.iter().map(|(_, name)| name))?;
+        let source = if kind == utils::ArchiveKind::Zip {
+            // Stream ZIP entries straight into ffmpeg instead of extracting them
+            observer.on_info(
+                RenderStage::ReadingArchive,
+                &format!("Streaming {} frames from zip", order.len()),
+            );
+            ffmpeg::source::FrameSource::ZipStream {
+                archive: input_path.clone(),
+                entries: order.iter().map(|&i| entries[i].0).collect(),
+                format: format.unwrap_or(input::ImageFormat::Png),
             }
-        }
+        } else {
+            let dir =
+                tempfile::tempdir().map_err(|e| RenderError::io("Failed to create temp dir", e))?;
+            let paths = utils::extract_archive_frames(input_path, &entries, dir.path(), cancel)?;
+            observer.on_info(
+                RenderStage::ReadingArchive,
+                &format!("Extracted {} frames from {}", paths.len(), kind),
+            );
+            _extracted_frames = Some(dir);
+            ffmpeg::source::FrameSource::Files(order.iter().map(|&i| paths[i].clone()).collect())
+        };
+        (source, order.len() as u32, issues)
+    } else {
+        observer.on_stage(RenderStage::CollectingFrames);
+        let mut frames = input::collect_input_frames(input_path, Some(pattern.clone()))?;
+        frames.retain(|f| input::ImageFormat::from_path(f).is_some_and(|f| formats.contains(&f)));
+        let numbering =
+            input::FrameNumbering::for_pattern(&input::FramePattern::new(&pattern)?, &frames);
+        let frames = range.select(frames, |f| numbering.number(f));
+        input::ImageFormat::of_sequence(&frames)?;
+        let numbers: Vec<_> = frames.iter().map(|f| numbering.number(f)).collect();
+        let (order, issues) = input::plan_sequence(&numbers, range.step, gap_policy)?;
+        // Pipe exactly the frames we collected, so ffmpeg never re-globs the
+        // folder with its own rules and the frame count always matches
+        let source =
+            ffmpeg::source::FrameSource::Files(order.iter().map(|&i| frames[i].clone()).collect());
+        (source, order.len() as u32, issues)
+    };
+
+    for issue in &sequence_issues {
+        observer.on_warning(&format!("Frame sequence: {}", issue));
     }
 
-    if !args.input.exists() {
-        return Err(format!(
-            "❌ Input path '{}' does not exist.",
-            args.input.display()
-        ));
+    if frame_count == 0 {
+        return Err(RenderError::NoFramesMatched {
+            dir: input_path.clone(),
+            pattern,
+        });
     }
 
-    let input_path = &args.input;
-    let (working_input_path, _temp_guard) = if input_path
-        .extension()
-        .map(|ext| ext == "zip")
-        .unwrap_or(false)
-    {
-        let (path, guard) =
-            utils::unzip_frames(input_path, args.verbose).map_err(|e| e.to_string())?;
-        (path, Some(guard))
+    let frame_check = if args.validate_frames.unwrap_or(true) {
+        observer.on_stage(RenderStage::ValidatingFrames);
+        let check = validate::validate_source(&source, args.decode_frames, cancel)?;
+        if let Some(check) = &check {
+            observer.on_info(
+                RenderStage::ValidatingFrames,
+                &format!("Checked {} frames: {}", check.frames_checked, check),
+            );
+        }
+        check
     } else {
-        (input_path.clone(), None)
+        None
     };
 
-    let pattern = args
-        .file_pattern
-        .clone()
-        .unwrap_or_else(|| "*.png".to_string());
-    let frames = input::collect_input_frames(&working_input_path, Some(pattern.clone()))
-        .map_err(|e| format!("❌ Failed to read frames: {}", e))?;
-    let frame_count = frames.len() as u32;
+    // Formats ffmpeg cannot split from a pipe are read from a numbered copy
+    let (source, _staged_frames) = if source.needs_staging() {
+        let dir =
+            tempfile::tempdir().map_err(|e| RenderError::io("Failed to create staging dir", e))?;
+        (source.stage(dir.path(), cancel)?, Some(dir))
+    } else {
+        (source, None)
+    };
 
-    // Use the provided file pattern when building the ffmpeg input string
-    let input_pattern = working_input_path.join(&pattern);
-    let input_str = input_pattern.to_str().unwrap();
+    let mut render_report = encode(
+        &args,
+        &source,
+        Some(frame_count as usize),
+        frame_check,
+        observer,
+        cancel,
+    )?;
+    render_report.sequence_issues = sequence_issues.iter().map(ToString::to_string).collect();
+    Ok(render_report)
+}
 
-    if frame_count == 0 {
-        return Err(format!(
-            "❌ No input files found in '{}' matching pattern '{}'.",
-            working_input_path.display(),
-            pattern
-        ));
+/// Validate the output path and format
+fn start_render(args: &RenderConfig) -> Result<(), RenderError> {
+    // Validate output path
+    if args.output.is_empty() {
+        return Err(RenderError::EmptyOutput);
     }
 
-    let duration = frame_count as f32 / args.fps as f32;
+    match args.format.as_str() {
+        "webm" | "webm-vp9" | "mp4" | "mov" | "gif" | "webp" | "apng" => Ok(()),
+        _ => Err(RenderError::UnsupportedFormat(args.format.clone())),
+    }
+}
 
-    let mut fade_filter = String::new();
-    if args.fade_in > 0.0 {
-        fade_filter.push_str(&format!("fade=t=in:st=0:d={}", args.fade_in));
+/// Check for ffmpeg availability upfront
+fn check_ffmpeg(
+    args: &RenderConfig,
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<(), RenderError> {
+    cancel.check()?;
+    observer.on_stage(RenderStage::CheckingFfmpeg);
+    let mut version_cmd = Command::new("ffmpeg");
+    version_cmd.arg("-version");
+    if !args.verbose_ffmpeg {
+        version_cmd.stdout(std::process::Stdio::null());
+        version_cmd.stderr(std::process::Stdio::null());
     }
-    if args.fade_out > 0.0 {
-        if !fade_filter.is_empty() {
-            fade_filter.push(',');
-        }
-        let start = (duration - args.fade_out).max(0.0);
-        fade_filter.push_str(&format!("fade=t=out:st={}:d={}", start, args.fade_out));
+    match version_cmd.status() {
+        Ok(s) if s.success() => Ok(()),
+        Ok(s) => Err(RenderError::FfmpegFailed {
+            exit_code: s.code(),
+            stderr: String::new(),
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(RenderError::FfmpegNotFound),
+        Err(e) => Err(RenderError::io("Failed to execute ffmpeg", e)),
     }
+}
 
-    if args.verbose {
-        println!(
-            "🌿 Rendering {} → {} at {} FPS...",
-            input_str, args.output, args.fps
-        );
+/// Encode `source` into the configured output format.
+///
+/// `frame_count` drives the fade-out timing and progress totals; when it is
+/// unknown, the number of frames actually fed to ffmpeg is reported instead.
+/// `frame_check` tells whether discarding alpha needs a warning.
+fn encode(
+    args: &RenderConfig,
+    source: &ffmpeg::source::FrameSource,
+    frame_count: Option<usize>,
+    frame_check: Option<validate::FrameCheck>,
+    observer: &mut dyn RenderObserver,
+    cancel: &CancellationToken,
+) -> Result<RenderReport, RenderError> {
+    let duration = match source {
+        ffmpeg::source::FrameSource::Video { info, .. } => info.duration.unwrap_or(0.0) as f32,
+        _ => frame_count.unwrap_or(0) as f32 / args.fps as f32,
+    };
+
+    let (filters, warnings) = output_filters(args, duration, frame_check.as_ref())?;
+    for warning in &warnings {
+        observer.on_warning(warning);
     }
+    observer.on_info(
+        RenderStage::Encoding,
+        &format!(
+            "Rendering {} → {} at {} FPS...",
+            source, args.output, args.fps
+        ),
+    );
 
-    let maybe_spinner = if args.verbose {
-        let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::with_template(
-                "{spinner:.green} 🌿 Rendering with FFmpeg... {elapsed_precise}",
-            )
-            .unwrap()
-            //.tick_chars("⠁⠃⠇⠧⠷⠿⠻⠹⠸⠰⠠   ⠟⠏⠛⠋  ⠻⠯⠷⠾⠽"),
-            .tick_chars("䷀䷫䷌䷅䷤䷥䷄䷍䷪"),
-        );
-        pb.enable_steady_tick(Duration::from_millis(120));
-        Some(pb)
-    } else {
-        None
+    let mut observer = WithFrameTotal {
+        inner: observer,
+        total: frame_count.map(|n| n as u64),
     };
 
-    let mut render_report = if args.format == "gif" {
-        ffmpeg::gif::render_gif(
-            input_str,
+    let mut render_report = match args.format.as_str() {
+        "gif" => ffmpeg::gif::render_gif(
+            source,
             &args.output,
             args.fps,
-            Some(&fade_filter),
+            &ffmpeg::gif::GifOptions::from_config(args),
+            &filters,
             args.verbose_ffmpeg,
-        )
-    } else {
-        ffmpeg::video::render_video(
-            input_str,
+            &mut observer,
+            cancel,
+        ),
+        "webp" => ffmpeg::webp::render_webp(
+            source,
+            &args.output,
+            args.fps,
+            args.lossless,
+            args.quality,
+            args.loop_count,
+            &filters,
+            args.verbose_ffmpeg,
+            &mut observer,
+            cancel,
+        ),
+        "apng" => ffmpeg::apng::render_apng(
+            source,
+            &args.output,
+            args.fps,
+            args.loop_count,
+            args.max_colors,
+            &filters,
+            args.verbose_ffmpeg,
+            &mut observer,
+            cancel,
+        ),
+        _ => ffmpeg::video::render_video(
+            source,
             &args.output,
             args.fps,
             &args.format,
             args.bitrate.as_deref(),
             args.crf,
-            Some(&fade_filter),
+            args.prores_profile.as_deref(),
+            &filters,
             args.verbose_ffmpeg,
-        )
+            &mut observer,
+            cancel,
+        ),
     }?;
 
+    for warning in warnings {
+        render_report.notes = Some(
+            render_report.notes.clone().unwrap_or_default() + &format!("\n⚠️ Warning: {}", warning),
+        );
+    }
+    render_report.frame_check = frame_check;
+
     // Post-inject known input frame count after rendering
-    render_report.frames_rendered = Some(frame_count as usize);
+    render_report.frames_rendered = match source {
+        ffmpeg::source::FrameSource::Memory(frames) if frame_count.is_none() => {
+            Some(frames.frames_fed())
+        }
+        _ => frame_count,
+    };
 
     if let Some(ext) = Path::new(&args.output).extension().and_then(|s| s.to_str()) {
         let ext = ext.to_lowercase();
-        let expected_ext = match args.format.as_str() {
-            "webm" => "webm",
-            "mp4" => "mp4",
-            "gif" => "gif",
-            _ => "",
+        let expected_exts: &[&str] = match args.format.as_str() {
+            "webm" | "webm-vp9" => &["webm"],
+            "mp4" => &["mp4"],
+            "mov" => &["mov"],
+            "gif" => &["gif"],
+            "webp" => &["webp"],
+            "apng" => &["png", "apng"],
+            _ => &[],
         };
 
-        if ext != expected_ext {
+        if !expected_exts.contains(&ext.as_str()) {
             let warning = format!(
-                "⚠️ Warning: Output extension '{}' does not match format '{}'",
+                "Output extension '{}' does not match format '{}'",
                 ext, args.format
             );
-            render_report.notes =
-                Some(render_report.notes.clone().unwrap_or_default() + &format!("\n{}", warning));
+            observer.on_warning(&warning);
+            render_report.notes = Some(
+                render_report.notes.clone().unwrap_or_default()
+                    + &format!("\n⚠️ Warning: {}", warning),
+            );
         }
     }
 
-    if let Some(pb) = &maybe_spinner {
-        pb.finish_with_message("✅ FFmpeg rendering complete!");
-    }
-
     if args.open {
         if let Err(e) = utils::open_output(&args.output) {
-            eprintln!("⚠️ Failed to open video preview: {}", e);
+            observer.on_warning(&format!("Failed to open video preview: {}", e));
         }
     }
     Ok(render_report)
 }
 
-/// Extract a single frame from an input folder or ZIP archive
+/// Build the filters applied before encoding: transforms, the background for
+/// formats that cannot keep transparency, and fades. Also returns the
+/// warnings to report.
+fn output_filters(
+    args: &RenderConfig,
+    duration: f32,
+    frame_check: Option<&validate::FrameCheck>,
+) -> Result<(ffmpeg::filter::FilterGraph, Vec<String>), RenderError> {
+    let mut transform = ffmpeg::transform::Transform::from_config(args)?;
+    let pix_fmt = match args.format.as_str() {
+        "gif" => {
+            transform = transform.with_default_width(ffmpeg::gif::DEFAULT_WIDTH);
+            "pal8"
+        }
+        "apng" => ffmpeg::apng::pixel_format(args.max_colors),
+        "webp" => ffmpeg::webp::pixel_format(args.lossless),
+        format => ffmpeg::video::pixel_format(format, args.prores_profile.as_deref())?,
+    };
+    let mut filters = ffmpeg::filter::FilterGraph::new();
+    // Subsampled chroma (yuv420p, ...) only encodes even frame sizes
+    for filter in transform.filters(ffmpeg::transform::is_subsampled(pix_fmt)) {
+        filters.push(filter);
+    }
+
+    let mut warnings: Vec<String> = frame_check
+        .iter()
+        .flat_map(|check| check.warnings.iter().cloned())
+        .collect();
+    let background = args
+        .background
+        .as_deref()
+        .map(ffmpeg::background::Background::parse)
+        .transpose()?;
+    let mut keeps_alpha = ffmpeg::background::has_alpha(pix_fmt);
+    let high_bit_depth = ffmpeg::video::is_high_bit_depth(pix_fmt);
+    match &background {
+        // Palettes keep on/off transparency at best, so a chosen background
+        // is still worth flattening anti-aliased edges onto
+        Some(background) if !keeps_alpha || ffmpeg::background::is_paletted(pix_fmt) => {
+            background.composite(&mut filters, args.fps, high_bit_depth);
+            keeps_alpha = false;
+        }
+        Some(background) => warnings.push(format!(
+            "Background '{}' ignored: '{}' output keeps transparency",
+            background, args.format
+        )),
+        None if !keeps_alpha && frame_check.is_some_and(|check| check.uses_alpha) => {
+            warnings.push(format!(
+                "Frames have alpha but '{}' output has none; transparent areas will turn black. \
+                 Set background to flatten them onto a color, checkerboard or image.",
+                args.format
+            ))
+        }
+        None => {}
+    }
+
+    // Alpha formats fade transparency; opaque ones fade from/to a color. A
+    // palette can't hold partial alpha, so gif and paletted apng count as opaque
+    let curve = ffmpeg::fade::FadeCurve::from_option(args.fade_curve.as_deref())?;
+    let target = ffmpeg::fade::FadeTarget::for_output(
+        keeps_alpha && !ffmpeg::background::is_paletted(pix_fmt),
+        args.fade_color.as_deref(),
+    )?;
+    let fades = [
+        (ffmpeg::filter::FadeDirection::In, 0.0, args.fade_in),
+        (
+            ffmpeg::filter::FadeDirection::Out,
+            (duration - args.fade_out).max(0.0),
+            args.fade_out,
+        ),
+    ];
+    for (direction, start, fade_duration) in fades {
+        if fade_duration > 0.0 {
+            let fade = ffmpeg::fade::Fade {
+                direction,
+                start,
+                duration: fade_duration,
+                curve,
+                target: target.clone(),
+                high_bit_depth,
+            };
+            for filter in fade.filters() {
+                filters.push(filter);
+            }
+        }
+    }
+    Ok((filters, warnings))
+}
+
+/// Forwards events, filling in the known input frame count on progress updates
+struct WithFrameTotal<'a> {
+    inner: &'a mut dyn RenderObserver,
+    total: Option<u64>,
+}
+
+impl RenderObserver for WithFrameTotal<'_> {
+    fn on_stage(&mut self, stage: RenderStage) {
+        self.inner.on_stage(stage);
+    }
+
+    fn on_frame_progress(&mut self, progress: &FfmpegProgress) {
+        let mut progress = progress.clone();
+        progress.total_frames = self.total.or(progress.total_frames);
+        self.inner.on_frame_progress(&progress);
+    }
+
+    fn on_warning(&mut self, message: &str) {
+        self.inner.on_warning(message);
+    }
+
+    fn on_finished(&mut self, result: Result<&RenderReport, &RenderError>) {
+        self.inner.on_finished(result);
+    }
+}
+
+/// Extract a single frame from an input folder, a ZIP/tar archive
+/// (optionally from `zip_subfolder` inside it) or a video file.
+///
+/// The frame is copied as-is, so when it is not a PNG the output extension is
+/// switched to match the frame (e.g. `preview.jpg`). Returns the written path.
 pub fn preview_frame(
     input: &std::path::Path,
     file_pattern: Option<String>,
+    zip_subfolder: Option<&str>,
+    formats: &[input::ImageFormat],
     frame_index: Option<usize>,
     output: &std::path::Path,
-    verbose: bool,
-) -> Result<String, String> {
+) -> Result<String, RenderError> {
     if !input.exists() {
-        return Err(format!(
-            "❌ Input path '{}' does not exist.",
-            input.display()
-        ));
+        return Err(RenderError::InputNotFound(input.to_path_buf()));
     }
 
-    if input.extension().map(|ext| ext == "zip").unwrap_or(false) {
-        let count = utils::count_pngs_in_zip(input).map_err(|e| e.to_string())?;
+    let archive = utils::ArchiveKind::of_path(input);
+    let video = match archive {
+        None if input.is_file() => Some(
+            ffmpeg::probe::probe_video(input)?
+                .ok_or_else(|| RenderError::UnrecognizedInput(input.to_path_buf()))?,
+        ),
+        _ => None,
+    };
+
+    let output = if let Some(info) = video {
+        let output = preview_output(output, Path::new("frame.png"));
+        preview_video_frame(input, &info, frame_index, &output)?;
+        output
+    } else if archive.is_some() {
+        let entries = utils::list_archive_frames(input, "*", zip_subfolder, formats)?;
+        let count = entries.len();
         if count == 0 {
-            return Err("❌ No PNG files found in zip archive".into());
+            return Err(RenderError::NoFramesMatched {
+                dir: zip_subfolder
+                    .map(|sub| input.join(sub))
+                    .unwrap_or_else(|| input.to_path_buf()),
+                pattern: input::ImageFormat::default_pattern(formats),
+            });
         }
         let idx = frame_index.unwrap_or(count / 2);
         if idx >= count {
-            return Err(format!(
-                "❌ Frame index {} out of range (0..{})",
-                idx,
-                count - 1
-            ));
+            return Err(RenderError::FrameIndexOutOfRange { index: idx, count });
         }
-        utils::extract_frame_from_zip(input, idx, output).map_err(|e| e.to_string())?;
+        let output = preview_output(output, Path::new(&entries[idx].1));
+        utils::extract_archive_frame(input, zip_subfolder, formats, idx, &output)?;
+        output
     } else {
-        let pattern = file_pattern.clone().unwrap_or_else(|| "*.png".to_string());
-        let frames = input::collect_input_frames(input, Some(pattern.clone()))
-            .map_err(|e| format!("❌ Failed to read frames: {}", e))?;
+        let pattern = file_pattern
+            .clone()
+            .unwrap_or_else(|| input::ImageFormat::default_pattern(formats));
+        let mut frames = input::collect_input_frames(input, Some(pattern.clone()))?;
+        frames.retain(|f| input::ImageFormat::from_path(f).is_some_and(|f| formats.contains(&f)));
         if frames.is_empty() {
-            return Err(format!(
-                "❌ No input files found in '{}' matching pattern '{}'",
-                input.display(),
-                pattern
-            ));
+            return Err(RenderError::NoFramesMatched {
+                dir: input.to_path_buf(),
+                pattern,
+            });
         }
         let idx = frame_index.unwrap_or(frames.len() / 2);
         if idx >= frames.len() {
-            return Err(format!(
-                "❌ Frame index {} out of range (0..{})",
-                idx,
-                frames.len() - 1
-            ));
+            return Err(RenderError::FrameIndexOutOfRange {
+                index: idx,
+                count: frames.len(),
+            });
+        }
+        let output = preview_output(output, &frames[idx]);
+        std::fs::copy(&frames[idx], &output)
+            .map_err(|e| RenderError::io("Failed to copy frame", e))?;
+        output
+    };
+
+    Ok(output.to_string_lossy().into_owned())
+}
+
+/// Extract frame `frame_index` (default: the middle frame) of a video to a PNG
+fn preview_video_frame(
+    input: &Path,
+    info: &ffmpeg::probe::VideoInfo,
+    frame_index: Option<usize>,
+    output: &Path,
+) -> Result<(), RenderError> {
+    let idx = frame_index.unwrap_or(info.frame_count.unwrap_or(0) / 2);
+    if let Some(count) = info.frame_count {
+        if idx >= count {
+            return Err(RenderError::FrameIndexOutOfRange { index: idx, count });
         }
-        std::fs::copy(&frames[idx], output)
-            .map_err(|e| format!("❌ Failed to copy frame: {}", e))?;
     }
+    let mut args = ffmpeg::source::FrameSource::Video {
+        path: input.to_path_buf(),
+        info: info.clone(),
+    }
+    .input_args(0);
+    args.extend([
+        "-vf".into(),
+        ffmpeg::filter::Filter::new("select")
+            .arg(format!("eq(n,{})", idx))
+            .to_string(),
+        "-frames:v".into(),
+        "1".into(),
+        "-y".into(),
+        output.to_string_lossy().into_owned(),
+        "-loglevel".into(),
+        "error".into(),
+    ]);
+    let _ = std::fs::remove_file(output);
+    utils::run_ffmpeg_with_output(&args, &mut |_| {}, &CancellationToken::new())?;
+    if !output.exists() {
+        // select matched nothing: the container under-reported its length
+        return Err(RenderError::FrameIndexOutOfRange {
+            index: idx,
+            count: info.frame_count.unwrap_or(0),
+        });
+    }
+    Ok(())
+}
 
-    if verbose {
-        println!("🖼️ Preview saved to: {}", output.display());
+/// Preview path for `frame`, keeping the frame's own image extension
+fn preview_output(output: &Path, frame: &Path) -> PathBuf {
+    match input::ImageFormat::from_path(frame) {
+        Some(input::ImageFormat::Png) | None => output.to_path_buf(),
+        Some(_) => output.with_extension(frame.extension().unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gif_config(background: Option<&str>) -> RenderConfig {
+        RenderConfig {
+            format: "gif".into(),
+            background: background.map(Into::into),
+            fade_in: 0.5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn gif_backgrounds_are_flattened() {
+        let (filters, warnings) = output_filters(&gif_config(Some("#336699")), 2.0, None).unwrap();
+        let graph = filters.to_string();
+        assert!(graph.contains("drawbox=color=0x336699:t=fill"), "{}", graph);
+        assert!(warnings.is_empty());
+
+        // Without a background, GIF keeps its on/off transparency silently
+        let (filters, warnings) = output_filters(&gif_config(None), 2.0, None).unwrap();
+        assert!(!filters.to_string().contains("overlay"));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn gif_fades_to_a_color() {
+        let mut config = gif_config(None);
+        config.fade_color = Some("#ffffff".into());
+        let (filters, _) = output_filters(&config, 2.0, None).unwrap();
+        let graph = filters.to_string();
+        assert!(
+            graph.contains("fade=t=in:st=0:d=0.5:color=0xffffff"),
+            "{}",
+            graph
+        );
+        assert!(!graph.contains("alpha=1"), "{}", graph);
+
+        // Paletted APNG too, while full RGBA APNG still fades transparency
+        config.format = "apng".into();
+        config.max_colors = Some(64);
+        let (filters, _) = output_filters(&config, 2.0, None).unwrap();
+        assert!(!filters.to_string().contains("alpha=1"));
+        config.max_colors = None;
+        let (filters, _) = output_filters(&config, 2.0, None).unwrap();
+        assert!(filters.to_string().contains("alpha=1"));
     }
-    Ok(output.to_string_lossy().into_owned())
 }
@@ -1,17 +1,22 @@
-use aether_renderer_core::RenderReport;
+use aether_renderer_core::{
+    FfmpegProgress, RenderConfig, RenderError, RenderObserver, RenderReport, RenderStage,
+};
 use clap::{CommandFactory, Parser};
+use indicatif::{ProgressBar, ProgressStyle};
 use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
 
 /// 🌸 Aether Renderer Core
 #[derive(Parser, Debug)]
 #[command(name = "aether-renderer")]
 #[command(about = "Render using configuration file or inline options", long_about = None)]
 struct Args {
-    /// Path to render configuration JSON
+    /// Path to render configuration JSON; other flags override its values
     #[arg(short, long)]
     config: Option<PathBuf>,
 
-    /// Input frames folder or ZIP
+    /// Input frames folder, ZIP/tar archive or video file
     #[arg(short, long)]
     input: Option<PathBuf>,
 
@@ -27,79 +32,334 @@ struct Args {
     #[arg(long)]
     fps: Option<u32>,
 
-    /// Optional format (e.g. webm, gif)
+    /// Optional format (webm, webm-vp9, mp4, mov, gif, webp, apng)
     #[arg(long)]
     format: Option<String>,
 
+    /// Seconds to fade in
+    #[arg(long, value_name = "SECONDS")]
+    fade_in: Option<f32>,
+
+    /// Seconds to fade out
+    #[arg(long, value_name = "SECONDS")]
+    fade_out: Option<f32>,
+
+    /// Fade timing: linear, ease-in, ease-out, ease-in-out or cubic
+    #[arg(long)]
+    fade_curve: Option<String>,
+
+    /// Hex color opaque formats fade from and to (default black)
+    #[arg(long)]
+    fade_color: Option<String>,
+
+    /// Target bitrate, e.g. 2500k
+    #[arg(long)]
+    bitrate: Option<String>,
+
+    /// Constant rate factor (lower = better); takes priority over bitrate
+    #[arg(long)]
+    crf: Option<u32>,
+
     /// Output video preview after rendering
-    #[arg(long, default_value_t = false)]
-    open: bool,
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    open: Option<bool>,
 
-    /// Extract a single frame instead of full render
-    #[arg(long, num_args = 0..=1, value_name = "N", value_parser = clap::value_parser!(u32))]
-    preview: Option<Option<u32>>,
+    /// Extract a single frame instead of full render (frame N, default the first)
+    #[arg(
+        long,
+        num_args = 0..=1,
+        value_name = "N",
+        default_missing_value = "0",
+        value_parser = clap::value_parser!(u32)
+    )]
+    preview: Option<u32>,
 
-    /// Enable verbose logging
+    /// Only use frames inside this folder of a ZIP or tar input
     #[arg(long)]
-    verbose: bool,
+    zip_subfolder: Option<String>,
 
-    /// Show full ffmpeg logs
+    /// Image types accepted as frames, comma separated (png, jpg, webp, tiff, exr, tga)
+    #[arg(long, value_delimiter = ',')]
+    input_extensions: Option<Vec<String>>,
+
+    /// What to do about gaps in frame numbers: skip, hold or fail
     #[arg(long)]
-    verbose_ffmpeg: bool,
+    on_frame_gaps: Option<String>,
+
+    /// First frame number to render (inclusive)
+    #[arg(long)]
+    start_frame: Option<u64>,
+
+    /// Last frame number to render (inclusive)
+    #[arg(long)]
+    end_frame: Option<u64>,
+
+    /// Render every Nth frame
+    #[arg(long)]
+    frame_step: Option<u32>,
+
+    /// ProRes profile for mov output: proxy, lt, standard, hq, 4444 or 4444xq
+    #[arg(long)]
+    prores_profile: Option<String>,
+
+    /// Encode webp output losslessly
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    lossless: Option<bool>,
+
+    /// Lossy webp quality from 0 to 100
+    #[arg(long)]
+    quality: Option<u32>,
+
+    /// Number of times animated output plays; 0 loops forever
+    #[arg(long)]
+    loop_count: Option<u32>,
+
+    /// Palette size for gif and apng
+    #[arg(long)]
+    max_colors: Option<u32>,
+
+    /// Output width in pixels
+    #[arg(long)]
+    width: Option<u32>,
+
+    /// Output height in pixels
+    #[arg(long)]
+    height: Option<u32>,
+
+    /// How frames fill width x height: contain, cover or stretch
+    #[arg(long)]
+    fit: Option<String>,
+
+    /// Rectangle cut from the source frames: W:H or W:H:X:Y
+    #[arg(long)]
+    crop: Option<String>,
+
+    /// Transparent margins: ALL, V:H or TOP:RIGHT:BOTTOM:LEFT
+    #[arg(long)]
+    pad: Option<String>,
+
+    /// Background for formats without alpha: hex color, checkerboard or image/video path
+    #[arg(long)]
+    background: Option<String>,
+
+    /// Scaling algorithm, e.g. lanczos or neighbor
+    #[arg(long)]
+    scale_flags: Option<String>,
+
+    /// GIF dithering mode, e.g. bayer, sierra2_4a or none
+    #[arg(long)]
+    dither: Option<String>,
+
+    /// GIF palette statistics mode: full or diff
+    #[arg(long)]
+    stats_mode: Option<String>,
+
+    /// Keep a transparent entry in the GIF palette
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    reserve_transparent: Option<bool>,
+
+    /// Read every frame's header before encoding
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    validate_frames: Option<bool>,
+
+    /// Fully decode every frame during validation (slower)
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    decode_frames: Option<bool>,
+
+    /// Enable verbose logging
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    verbose: Option<bool>,
+
+    /// Show full ffmpeg logs
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, default_missing_value = "true")]
+    verbose_ffmpeg: Option<bool>,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
+impl Args {
+    /// Overwrite the config values that were given on the command line
+    fn apply(self, cfg: &mut RenderConfig) {
+        fn set<T>(field: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        fn set_some<T>(field: &mut Option<T>, value: Option<T>) {
+            if value.is_some() {
+                *field = value;
+            }
+        }
+
+        set(&mut cfg.input, self.input);
+        set(
+            &mut cfg.output,
+            self.output.map(|o| o.to_string_lossy().into_owned()),
+        );
+        set(&mut cfg.fps, self.fps);
+        set(&mut cfg.format, self.format);
+        set(&mut cfg.fade_in, self.fade_in);
+        set(&mut cfg.fade_out, self.fade_out);
+        set_some(&mut cfg.fade_curve, self.fade_curve);
+        set_some(&mut cfg.fade_color, self.fade_color);
+        set_some(&mut cfg.bitrate, self.bitrate);
+        set_some(&mut cfg.crf, self.crf);
+        set(&mut cfg.open, self.open);
+        set_some(&mut cfg.preview, self.preview.map(|n| n as usize));
+        set_some(&mut cfg.file_pattern, self.file_pattern);
+        set_some(&mut cfg.zip_subfolder, self.zip_subfolder);
+        set_some(&mut cfg.input_extensions, self.input_extensions);
+        set_some(&mut cfg.on_frame_gaps, self.on_frame_gaps);
+        set_some(&mut cfg.start_frame, self.start_frame);
+        set_some(&mut cfg.end_frame, self.end_frame);
+        set_some(&mut cfg.frame_step, self.frame_step);
+        set_some(&mut cfg.prores_profile, self.prores_profile);
+        set(&mut cfg.lossless, self.lossless);
+        set_some(&mut cfg.quality, self.quality);
+        set_some(&mut cfg.loop_count, self.loop_count);
+        set_some(&mut cfg.max_colors, self.max_colors);
+        set_some(&mut cfg.width, self.width);
+        set_some(&mut cfg.height, self.height);
+        set_some(&mut cfg.fit, self.fit);
+        set_some(&mut cfg.crop, self.crop);
+        set_some(&mut cfg.pad, self.pad);
+        set_some(&mut cfg.background, self.background);
+        set_some(&mut cfg.scale_flags, self.scale_flags);
+        set_some(&mut cfg.dither, self.dither);
+        set_some(&mut cfg.stats_mode, self.stats_mode);
+        set_some(&mut cfg.reserve_transparent, self.reserve_transparent);
+        set_some(&mut cfg.validate_frames, self.validate_frames);
+        set(&mut cfg.decode_frames, self.decode_frames);
+        set(&mut cfg.verbose, self.verbose);
+        set(&mut cfg.verbose_ffmpeg, self.verbose_ffmpeg);
+    }
+}
 
-    if let Some(config) = args.config {
-        if args.verbose {
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    // The config may still switch verbose logging on or off below
+    let verbose = args.verbose.unwrap_or(false);
+    let mut cfg = if let Some(config) = &args.config {
+        if verbose {
             println!("Loading config from {}", config.display());
         }
+        RenderConfig::from_file(&config.to_string_lossy())?
+    } else if args.input.is_some() {
+        if verbose {
+            println!("Rendering from CLI arguments");
+        }
+        RenderConfig {
+            output: "output.webm".into(),
+            ..Default::default()
+        }
+    } else {
+        Args::command().print_help()?;
+        println!();
+        return Err("No input provided".into());
+    };
+    args.apply(&mut cfg);
 
-        return match aether_renderer_core::render_from_config(config.to_str().unwrap()) {
-            Ok(report) => {
-                get_render_report(report, args.verbose);
-                Ok(())
-            }
-            Err(e) => Err(e.into()),
-        };
+    let verbose = cfg.verbose;
+    if verbose {
+        let version = env!("CARGO_PKG_VERSION");
+        eprintln!("🪼 Aether Renderer v{version} starting...");
     }
+    let mut observer = CliObserver::new(&cfg);
+    let report = aether_renderer_core::render_with_observer(cfg, &mut observer)?;
+    get_render_report(report, verbose);
+    Ok(())
+}
 
-    if let Some(input) = args.input {
-        let output = args.output.unwrap_or_else(|| PathBuf::from("output.webm"));
+/// Prints render events: warnings always, stage details and a frame
+/// progress bar with ETA when verbose
+struct CliObserver {
+    verbose: bool,
+    verbose_ffmpeg: bool,
+    // Created on the first update so previews don't draw an empty bar
+    bar: Option<ProgressBar>,
+}
 
-        if args.verbose {
-            println!("Rendering from CLI arguments");
+impl CliObserver {
+    fn new(cfg: &RenderConfig) -> Self {
+        CliObserver {
+            verbose: cfg.verbose,
+            verbose_ffmpeg: cfg.verbose_ffmpeg,
+            bar: None,
         }
+    }
 
-        let cfg = aether_renderer_core::RenderConfig {
-            input,
-            output: output.to_string_lossy().into_owned(),
-            fps: args.fps.unwrap_or(30),
-            format: args.format.unwrap_or_else(|| "webm".into()),
-            fade_in: 0.0, // or add to Args if needed
-            fade_out: 0.0,
-            bitrate: None,
-            crf: None,
-            open: args.open,
-            preview: args.preview.flatten().map(|n| n as usize),
-            file_pattern: args.file_pattern,
-            verbose: args.verbose,
-            verbose_ffmpeg: args.verbose_ffmpeg,
-        };
+    /// Run `print` with the progress bar hidden, so lines don't tear it
+    fn print(&self, print: impl FnOnce()) {
+        match &self.bar {
+            Some(pb) => pb.suspend(print),
+            None => print(),
+        }
+    }
+}
 
-        return match aether_renderer_core::render(cfg) {
-            Ok(report) => {
-                get_render_report(report, args.verbose);
-                Ok(())
-            }
-            Err(e) => Err(e.into()),
+impl RenderObserver for CliObserver {
+    fn on_stage(&mut self, stage: RenderStage) {
+        if stage == RenderStage::CheckingFfmpeg && self.verbose_ffmpeg {
+            self.print(|| println!("🔍 Checking for ffmpeg..."));
+        }
+    }
+
+    fn on_frame_progress(&mut self, update: &FfmpegProgress) {
+        if !self.verbose {
+            return;
+        }
+        let pb = self.bar.get_or_insert_with(|| {
+            let pb = ProgressBar::new(update.total_frames.unwrap_or(0));
+            pb.set_style(
+                ProgressStyle::with_template(
+                    "{spinner:.green} 🌿 Rendering [{bar:30.green/white}] {pos}/{len} frames \
+                     {elapsed_precise} ETA {eta} {msg}",
+                )
+                .unwrap()
+                .tick_chars("䷀䷫䷌䷅䷤䷥䷄䷍䷪")
+                .progress_chars("=> "),
+            );
+            pb.enable_steady_tick(Duration::from_millis(120));
+            pb
+        });
+        pb.set_position(update.frame);
+        if let Some(speed) = update.speed {
+            pb.set_message(format!("{:.2}x", speed));
+        }
+    }
+
+    fn on_info(&mut self, stage: RenderStage, message: &str) {
+        if !self.verbose {
+            return;
+        }
+        let icon = match stage {
+            RenderStage::ReadingArchive => "📦",
+            RenderStage::ValidatingFrames => "🔎",
+            RenderStage::ExtractingPreview => "🖼️",
+            _ => "🌿",
         };
+        self.print(|| println!("{} {}", icon, message));
+    }
+
+    fn on_warning(&mut self, message: &str) {
+        self.print(|| eprintln!("⚠️ {}", message));
     }
 
-    Args::command().print_help()?;
-    println!();
-    Err("No input provided".into())
+    fn on_finished(&mut self, result: Result<&RenderReport, &RenderError>) {
+        if let Some(pb) = self.bar.take() {
+            match result {
+                Ok(_) => pb.finish_with_message("✅ FFmpeg rendering complete!"),
+                Err(_) => pb.abandon(),
+            }
+        }
+    }
 }
 
 fn get_render_report(report: RenderReport, verbose: bool) {
@@ -112,3 +372,126 @@ fn get_render_report(report: RenderReport, verbose: bool) {
         println!("{}", report.summary());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_from(cli: &[&str], mut cfg: RenderConfig) -> RenderConfig {
+        let args =
+            Args::try_parse_from(std::iter::once("aether-renderer").chain(cli.iter().copied()))
+                .unwrap();
+        args.apply(&mut cfg);
+        cfg
+    }
+
+    #[test]
+    fn cli_flags_override_only_the_given_config_values() {
+        let base = RenderConfig {
+            input: "frames".into(),
+            output: "from_config.mp4".into(),
+            format: "mp4".into(),
+            fade_in: 1.0,
+            crf: Some(18),
+            width: Some(1280),
+            reserve_transparent: Some(true),
+            ..Default::default()
+        };
+        let cfg = config_from(
+            &[
+                "--config",
+                "render.json",
+                "--fade-out",
+                "0.5",
+                "--crf",
+                "23",
+                "--background",
+                "#ffffff",
+                "--input-extensions",
+                "jpg,webp",
+                "--reserve-transparent",
+                "false",
+            ],
+            base,
+        );
+        assert_eq!(cfg.output, "from_config.mp4");
+        assert_eq!(cfg.format, "mp4");
+        assert_eq!(cfg.fade_in, 1.0);
+        assert_eq!(cfg.fade_out, 0.5);
+        assert_eq!(cfg.crf, Some(23));
+        assert_eq!(cfg.width, Some(1280));
+        assert_eq!(cfg.background.as_deref(), Some("#ffffff"));
+        assert_eq!(
+            cfg.input_extensions,
+            Some(vec!["jpg".to_string(), "webp".to_string()])
+        );
+        assert_eq!(cfg.reserve_transparent, Some(false));
+    }
+
+    #[test]
+    fn inline_flags_fill_a_default_config() {
+        let cfg = config_from(
+            &[
+                "--input",
+                "frames",
+                "--output",
+                "out.gif",
+                "--format",
+                "gif",
+                "--fade-in",
+                "0.25",
+                "--bitrate",
+                "2M",
+                "--lossless",
+                "--verbose",
+                "true",
+            ],
+            RenderConfig::default(),
+        );
+        assert_eq!(cfg.input, PathBuf::from("frames"));
+        assert_eq!(cfg.output, "out.gif");
+        assert_eq!(cfg.fps, 30);
+        assert_eq!(cfg.fade_in, 0.25);
+        assert_eq!(cfg.bitrate.as_deref(), Some("2M"));
+        assert!(cfg.lossless);
+        assert!(cfg.verbose);
+    }
+
+    #[test]
+    fn bare_preview_flag_previews_the_first_frame() {
+        let cfg = config_from(&["--input", "frames", "--preview"], RenderConfig::default());
+        assert_eq!(cfg.preview, Some(0));
+        let cfg = config_from(&["--preview", "12", "--verbose"], RenderConfig::default());
+        assert_eq!(cfg.preview, Some(12));
+        assert!(cfg.verbose);
+        assert_eq!(config_from(&[], RenderConfig::default()).preview, None);
+    }
+
+    #[test]
+    fn bool_flags_can_turn_config_values_off() {
+        let base = RenderConfig {
+            open: true,
+            lossless: true,
+            verbose: true,
+            verbose_ffmpeg: true,
+            ..Default::default()
+        };
+        let cfg = config_from(
+            &[
+                "--config",
+                "render.json",
+                "--open",
+                "false",
+                "--lossless=false",
+                "--verbose",
+                "false",
+            ],
+            base,
+        );
+        assert!(!cfg.open);
+        assert!(!cfg.lossless);
+        assert!(!cfg.verbose);
+        // Flags that weren't given keep the config value
+        assert!(cfg.verbose_ffmpeg);
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt;
+
+use crate::error::RenderError;
+use crate::progress::FfmpegProgress;
+use crate::report::RenderReport;
+
+/// A step of the render pipeline, reported through [`RenderObserver::on_stage`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderStage {
+    CheckingFfmpeg,
+    ProbingVideo,
+    ReadingArchive,
+    CollectingFrames,
+    ValidatingFrames,
+    ExtractingPreview,
+    GeneratingPalette,
+    Encoding,
+}
+
+impl fmt::Display for RenderStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            RenderStage::CheckingFfmpeg => "Checking for ffmpeg",
+            RenderStage::ProbingVideo => "Inspecting video input",
+            RenderStage::ReadingArchive => "Reading frames from archive",
+            RenderStage::CollectingFrames => "Collecting input frames",
+            RenderStage::ValidatingFrames => "Validating input frames",
+            RenderStage::ExtractingPreview => "Extracting preview frame",
+            RenderStage::GeneratingPalette => "Generating GIF palette",
+            RenderStage::Encoding => "Encoding with ffmpeg",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Receives events while a render runs, so GUIs can show status without
+/// parsing stdout. Every method has an empty default implementation.
+pub trait RenderObserver {
+    /// A new pipeline stage has started
+    fn on_stage(&mut self, _stage: RenderStage) {}
+
+    /// ffmpeg reported encoding progress
+    fn on_frame_progress(&mut self, _progress: &FfmpegProgress) {}
+
+    /// A detail of the current stage, such as how many frames were read,
+    /// meant for verbose output
+    fn on_info(&mut self, _stage: RenderStage, _message: &str) {}
+
+    /// A non-fatal problem was detected
+    fn on_warning(&mut self, _message: &str) {}
+
+    /// The render finished, successfully or not
+    fn on_finished(&mut self, _result: Result<&RenderReport, &RenderError>) {}
+}
+
+/// Closures observe frame progress only
+impl<F> RenderObserver for F
+where
+    F: FnMut(&FfmpegProgress),
+{
+    fn on_frame_progress(&mut self, progress: &FfmpegProgress) {
+        self(progress)
+    }
+}
+
+/// Observer that ignores every event
+pub struct NoopObserver;
+
+impl RenderObserver for NoopObserver {}
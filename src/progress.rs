@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+/// A progress update parsed from ffmpeg's `-progress` output
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FfmpegProgress {
+    /// Frames encoded so far
+    pub frame: u64,
+    /// Total frames expected, when known by the caller
+    pub total_frames: Option<u64>,
+    /// Timestamp of the last encoded frame
+    pub out_time: Duration,
+    /// Current encoding speed relative to realtime (e.g. `2.5` for `2.5x`)
+    pub speed: Option<f32>,
+    /// Current encoding rate in frames per second
+    pub fps: Option<f32>,
+    /// True for the final update, once ffmpeg reports `progress=end`
+    pub done: bool,
+}
+
+impl FfmpegProgress {
+    /// Fraction of frames encoded in `0.0..=1.0`, if the total is known
+    pub fn fraction(&self) -> Option<f32> {
+        match self.total_frames {
+            Some(total) if total > 0 => Some((self.frame as f32 / total as f32).min(1.0)),
+            _ => None,
+        }
+    }
+}
+
+/// Incremental parser for the `key=value` blocks ffmpeg writes with `-progress`.
+///
+/// Each block ends with a `progress=continue` or `progress=end` line, at which
+/// point a complete [`FfmpegProgress`] is returned.
+#[derive(Debug, Default)]
+pub struct ProgressParser {
+    current: FfmpegProgress,
+}
+
+impl ProgressParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a single line of `-progress` output
+    pub fn feed(&mut self, line: &str) -> Option<FfmpegProgress> {
+        let (key, value) = line.trim().split_once('=')?;
+        let value = value.trim();
+        match key {
+            "frame" => {
+                if let Ok(frame) = value.parse() {
+                    self.current.frame = frame;
+                }
+            }
+            "fps" => self.current.fps = value.parse().ok(),
+            // Despite the name, `out_time_ms` is also reported in microseconds
+            "out_time_us" | "out_time_ms" => {
+                if let Ok(us) = value.parse::<u64>() {
+                    self.current.out_time = Duration::from_micros(us);
+                }
+            }
+            "speed" => self.current.speed = value.trim_end_matches('x').parse().ok(),
+            "progress" => {
+                self.current.done = value == "end";
+                return Some(self.current.clone());
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProgressParser;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_progress_blocks() {
+        let output = "frame=12\nfps=24.00\nout_time_us=500000\nout_time=00:00:00.500000\n\
+                      speed=1.5x\nprogress=continue\nframe=24\nout_time_us=1000000\n\
+                      speed=N/A\nprogress=end\n";
+        let mut parser = ProgressParser::new();
+        let updates: Vec<_> = output.lines().filter_map(|l| parser.feed(l)).collect();
+
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].frame, 12);
+        assert_eq!(updates[0].fps, Some(24.0));
+        assert_eq!(updates[0].out_time, Duration::from_millis(500));
+        assert_eq!(updates[0].speed, Some(1.5));
+        assert!(!updates[0].done);
+        assert_eq!(updates[1].frame, 24);
+        assert_eq!(updates[1].speed, None);
+        assert!(updates[1].done);
+    }
+
+    #[test]
+    fn fraction_requires_total() {
+        let mut parser = ProgressParser::new();
+        parser.feed("frame=5");
+        let mut update = parser.feed("progress=continue").unwrap();
+        assert_eq!(update.fraction(), None);
+        update.total_frames = Some(10);
+        assert_eq!(update.fraction(), Some(0.5));
+    }
+}
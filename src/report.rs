@@ -1,5 +1,7 @@
 use std::path::PathBuf;
 
+use crate::validate::FrameCheck;
+
 #[derive(Debug)]
 pub struct RenderReport {
     pub output_path: PathBuf,
@@ -7,6 +9,10 @@ pub struct RenderReport {
     pub ffmpeg_warnings: Vec<String>,
     pub preview: bool,
     pub notes: Option<String>,
+    /// Gaps and duplicates found in the input frame numbering
+    pub sequence_issues: Vec<String>,
+    /// Size, color type and alpha use found by the pre-flight frame check
+    pub frame_check: Option<FrameCheck>,
 }
 
 impl RenderReport {
@@ -23,6 +29,8 @@ impl RenderReport {
             ffmpeg_warnings,
             preview,
             notes,
+            sequence_issues: Vec::new(),
+            frame_check: None,
         }
     }
 
@@ -42,6 +50,17 @@ impl RenderReport {
             }
         }
 
+        if !self.sequence_issues.is_empty() {
+            summary.push_str("⚠️ Frame sequence issues:\n");
+            for issue in &self.sequence_issues {
+                summary.push_str(&format!("- {}\n", issue));
+            }
+        }
+
+        if let Some(check) = &self.frame_check {
+            summary.push_str(&format!("Input frames: {}\n", check));
+        }
+
         if self.preview {
             summary.push_str("🔍 Preview mode enabled.\n");
         }
@@ -1,135 +1,386 @@
+use std::collections::HashMap;
 use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, ExitStatus};
-use tempfile::tempdir;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
 use zip::ZipArchive;
 
-fn is_valid_image(file_name: &str) -> bool {
-    let name = file_name.to_lowercase();
-    name.ends_with(".png") && !name.starts_with("._")
+use crate::cancel::CancellationToken;
+use crate::error::RenderError;
+use crate::input::{natural_cmp, FramePattern, ImageFormat};
+use crate::progress::{FfmpegProgress, ProgressParser};
+
+fn is_valid_image(file_name: &str, formats: &[ImageFormat]) -> bool {
+    !file_name.starts_with("._")
+        && ImageFormat::from_path(Path::new(file_name)).is_some_and(|f| formats.contains(&f))
 }
 
-/// Extracts `frame_*.png` from a ZIP into a temporary folder and returns the
-/// folder path along with the temp directory guard.
-pub fn unzip_frames(
-    zip_path: &Path,
-    verbose: bool,
-) -> Result<(PathBuf, tempfile::TempDir), Box<dyn std::error::Error>> {
-    let file = File::open(zip_path)
-        .map_err(|e| format!("❌ Failed to open zip file '{}': {}", zip_path.display(), e))?;
+/// Archive formats accepted as frame input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+    TarZst,
+}
 
-    let mut archive =
-        ZipArchive::new(file).map_err(|e| format!("❌ Failed to read zip archive: {}", e))?;
+impl ArchiveKind {
+    /// Detect an archive from its first bytes rather than its extension.
+    /// Returns `None` for folders, unreadable files and anything else.
+    pub fn detect(path: &Path) -> Option<ArchiveKind> {
+        if !path.is_file() {
+            return None;
+        }
+        let mut header = Vec::with_capacity(512);
+        File::open(path)
+            .ok()?
+            .take(512)
+            .read_to_end(&mut header)
+            .ok()?;
+        match header.as_slice() {
+            [b'P', b'K', 3, 4, ..] | [b'P', b'K', 5, 6, ..] => Some(ArchiveKind::Zip),
+            [0x1f, 0x8b, ..] => Some(ArchiveKind::TarGz),
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(ArchiveKind::TarZst),
+            h if h.get(257..262) == Some(b"ustar") => Some(ArchiveKind::Tar),
+            _ => None,
+        }
+    }
 
-    let temp_dir = tempdir().map_err(|e| format!("❌ Failed to create temp dir: {}", e))?;
-    let temp_path = temp_dir.path().to_path_buf();
+    /// The archive kind an extension claims: `.zip`, `.tar`, `.tar.gz`/`.tgz`
+    /// or `.tar.zst`/`.tzst`
+    pub fn from_extension(path: &Path) -> Option<ArchiveKind> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        [
+            (".zip", ArchiveKind::Zip),
+            (".tar", ArchiveKind::Tar),
+            (".tar.gz", ArchiveKind::TarGz),
+            (".tgz", ArchiveKind::TarGz),
+            (".tar.zst", ArchiveKind::TarZst),
+            (".tzst", ArchiveKind::TarZst),
+        ]
+        .into_iter()
+        .find(|(ext, _)| name.ends_with(ext))
+        .map(|(_, kind)| kind)
+    }
 
-    let mut extracted = 0u32;
-    for i in 0..archive.len() {
-        let mut file = archive
-            .by_index(i)
-            .map_err(|e| format!("❌ Failed to access file in zip at index {}: {}", i, e))?;
+    /// Detect an archive by content, falling back to its extension so a
+    /// truncated or corrupt archive still reaches the archive reader and
+    /// fails with a real archive error
+    pub fn of_path(path: &Path) -> Option<ArchiveKind> {
+        if !path.is_file() {
+            return None;
+        }
+        Self::detect(path).or_else(|| Self::from_extension(path))
+    }
+}
 
-        let filename = file.name().rsplit('/').next().unwrap_or("");
-        if !is_valid_image(filename) {
+impl std::fmt::Display for ArchiveKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ArchiveKind::Zip => "zip",
+            ArchiveKind::Tar => "tar",
+            ArchiveKind::TarGz => "tar.gz",
+            ArchiveKind::TarZst => "tar.zst",
+        })
+    }
+}
+
+/// Call `visit` with the index, path and contents of every file entry of the
+/// archive at `path`, in archive order, until it returns `false`.
+///
+/// The archive kind comes from [`ArchiveKind::of_path`]; anything else is
+/// rejected with [`RenderError::UnrecognizedInput`].
+fn for_each_archive_entry(
+    path: &Path,
+    mut visit: impl FnMut(usize, &str, &mut dyn Read) -> Result<bool, RenderError>,
+) -> Result<(), RenderError> {
+    let kind =
+        ArchiveKind::of_path(path).ok_or_else(|| RenderError::UnrecognizedInput(path.into()))?;
+    let file = File::open(path).map_err(|e| {
+        RenderError::io(
+            format!("Failed to open {} file '{}'", kind, path.display()),
+            e,
+        )
+    })?;
+    let tar_error = |e| {
+        RenderError::io(
+            format!("Failed to read {} file '{}'", kind, path.display()),
+            e,
+        )
+    };
+    let reader: Box<dyn Read> = match kind {
+        ArchiveKind::Zip => {
+            let mut archive = ZipArchive::new(file)?;
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index)?;
+                if entry.is_dir() {
+                    continue;
+                }
+                let name = entry.name().to_string();
+                if !visit(index, &name, &mut entry)? {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+        ArchiveKind::Tar => Box::new(file),
+        ArchiveKind::TarGz => Box::new(flate2::read::GzDecoder::new(file)),
+        ArchiveKind::TarZst => Box::new(zstd::stream::read::Decoder::new(file).map_err(tar_error)?),
+    };
+    let mut archive = tar::Archive::new(reader);
+    for (index, entry) in archive.entries().map_err(tar_error)?.enumerate() {
+        let mut entry = entry.map_err(tar_error)?;
+        if !entry.header().entry_type().is_file() {
             continue;
         }
+        let name = entry
+            .path()
+            .map_err(tar_error)?
+            .to_string_lossy()
+            .into_owned();
+        let name = name.trim_start_matches("./").to_string();
+        if !visit(index, &name, &mut entry)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// A frame image inside an archive
+struct ArchiveFrame {
+    index: usize,
+    /// Full in-archive path, e.g. `shotA/0001.png`
+    path: String,
+}
+
+impl ArchiveFrame {
+    fn file_name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or("")
+    }
+}
+
+/// Collect frame images of the given `formats` from the archive at
+/// `archive_path`, optionally limited to `subfolder` and to file names
+/// matching `pattern`, sorted naturally by their full in-archive path.
+///
+/// Two frames sharing a file name in different folders make the sequence
+/// ambiguous, so that is reported as [`RenderError::DuplicateFrameName`].
+fn archive_frame_entries(
+    archive_path: &Path,
+    subfolder: Option<&str>,
+    formats: &[ImageFormat],
+    pattern: Option<&FramePattern>,
+) -> Result<Vec<ArchiveFrame>, RenderError> {
+    let prefix = subfolder
+        .map(|s| s.trim_matches('/'))
+        .filter(|s| !s.is_empty())
+        .map(|s| format!("{}/", s));
+
+    let mut frames = Vec::new();
+    for_each_archive_entry(archive_path, |index, name, _| {
+        if name.starts_with("__MACOSX/") {
+            return Ok(true);
+        }
+        let frame = ArchiveFrame {
+            index,
+            path: name.to_string(),
+        };
+        if let Some(prefix) = &prefix {
+            if !frame.path.starts_with(prefix.as_str()) {
+                return Ok(true);
+            }
+        }
+        if !is_valid_image(frame.file_name(), formats) {
+            return Ok(true);
+        }
+        if let Some(pattern) = pattern {
+            if !pattern.matches(frame.file_name()) {
+                return Ok(true);
+            }
+        }
+        frames.push(frame);
+        Ok(true)
+    })?;
+    frames.sort_by(|a, b| natural_cmp(&a.path, &b.path));
+
+    let mut seen: HashMap<&str, &str> = HashMap::new();
+    for frame in &frames {
+        if let Some(first) = seen.insert(frame.file_name(), &frame.path) {
+            return Err(RenderError::DuplicateFrameName {
+                name: frame.file_name().to_string(),
+                first: first.to_string(),
+                second: frame.path.clone(),
+            });
+        }
+    }
+    Ok(frames)
+}
 
-        let full_out_path = temp_path.join(filename);
-        let mut out_file = File::create(&full_out_path).map_err(|e| {
-            format!(
-                "❌ Failed to create output file '{}': {}",
-                full_out_path.display(),
-                e
+/// Write the listed archive frames (as returned by [`list_archive_frames`])
+/// into `dir` under their file names, in a single pass over the archive.
+/// Returns the written paths in the order of `entries`.
+pub fn extract_archive_frames(
+    archive_path: &Path,
+    entries: &[(usize, String)],
+    dir: &Path,
+    cancel: &CancellationToken,
+) -> Result<Vec<PathBuf>, RenderError> {
+    let wanted: HashMap<usize, PathBuf> = entries
+        .iter()
+        .map(|(index, name)| {
+            let file_name = name.rsplit('/').next().unwrap_or(name);
+            (*index, dir.join(file_name))
+        })
+        .collect();
+    let mut remaining = wanted.len();
+    for_each_archive_entry(archive_path, |index, _, content| {
+        cancel.check()?;
+        let Some(out_path) = wanted.get(&index) else {
+            return Ok(true);
+        };
+        let mut out_file = File::create(out_path).map_err(|e| {
+            RenderError::io(
+                format!("Failed to create output file '{}'", out_path.display()),
+                e,
             )
         })?;
-
-        std::io::copy(&mut file, &mut out_file).map_err(|e| {
-            format!(
-                "❌ Failed to copy content to '{}': {}",
-                full_out_path.display(),
-                e
+        std::io::copy(content, &mut out_file).map_err(|e| {
+            RenderError::io(
+                format!("Failed to copy content to '{}'", out_path.display()),
+                e,
             )
         })?;
+        remaining -= 1;
+        Ok(remaining > 0)
+    })?;
+    Ok(entries
+        .iter()
+        .map(|(index, _)| wanted[index].clone())
+        .collect())
+}
 
-        if verbose {
-            println!("✅ Extracting: {}", full_out_path.display());
-        }
-        extracted += 1;
-    }
+/// List the image entries of a ZIP or tar archive (or of `subfolder` inside
+/// it) in one of `formats` whose file name matches `pattern`, as
+/// `(index, in-archive path)` pairs sorted by full path.
+pub fn list_archive_frames(
+    archive_path: &Path,
+    pattern: &str,
+    subfolder: Option<&str>,
+    formats: &[ImageFormat],
+) -> Result<Vec<(usize, String)>, RenderError> {
+    let pattern = FramePattern::new(pattern)?;
+    let frames = archive_frame_entries(archive_path, subfolder, formats, Some(&pattern))?;
+    Ok(frames.into_iter().map(|f| (f.index, f.path)).collect())
+}
 
-    if extracted == 0 {
-        return Err("❌ No PNG files found in zip archive".into());
+/// Extract a specific frame of one of `formats` from a ZIP or tar archive,
+/// counting frames in the same full-path order used for rendering
+pub fn extract_archive_frame(
+    archive_path: &Path,
+    subfolder: Option<&str>,
+    formats: &[ImageFormat],
+    frame_index: usize,
+    output: &Path,
+) -> Result<(), RenderError> {
+    let frames = archive_frame_entries(archive_path, subfolder, formats, None)?;
+    if frames.is_empty() {
+        return Err(RenderError::NoFramesMatched {
+            dir: subfolder
+                .map(|sub| archive_path.join(sub))
+                .unwrap_or_else(|| archive_path.to_path_buf()),
+            pattern: ImageFormat::default_pattern(formats),
+        });
     }
-
-    if verbose {
-        if extracted > 1 {
-            println!("⚠️  Extracted {} frames from zip", extracted);
-        } else {
-            println!("✅ Extracted 1 frame from zip");
+    let Some(frame) = frames.get(frame_index) else {
+        return Err(RenderError::FrameIndexOutOfRange {
+            index: frame_index,
+            count: frames.len(),
+        });
+    };
+    for_each_archive_entry(archive_path, |index, _, content| {
+        if index != frame.index {
+            return Ok(true);
         }
-        println!("🗂️  Extracted frames to: {}", temp_path.display());
-    }
-    Ok((temp_path.clone(), temp_dir))
+        let mut out = File::create(output).map_err(|e| {
+            RenderError::io(
+                format!("Failed to create output file '{}'", output.display()),
+                e,
+            )
+        })?;
+        std::io::copy(content, &mut out).map_err(|e| {
+            RenderError::io(
+                format!("Failed to copy content to '{}'", output.display()),
+                e,
+            )
+        })?;
+        Ok(false)
+    })
 }
 
-/// Count PNG files inside a ZIP archive
-pub fn count_pngs_in_zip(zip_path: &Path) -> Result<usize, Box<dyn std::error::Error>> {
-    let file = File::open(zip_path)
-        .map_err(|e| format!("❌ Failed to open zip file '{}': {}", zip_path.display(), e))?;
-    let mut archive =
-        ZipArchive::new(file).map_err(|e| format!("❌ Failed to read zip archive: {}", e))?;
-    let mut count = 0usize;
-    for i in 0..archive.len() {
-        let file = archive.by_index(i)?;
-        let filename = file.name().rsplit('/').next().unwrap_or("");
-        if is_valid_image(filename) {
-            count += 1;
-        }
+/// Extracts the PNG frames of a ZIP or tar archive (or of `subfolder` inside
+/// it) into a temporary folder and returns the folder path along with the
+/// temp directory guard. `_verbose` is ignored; progress is reported through
+/// [`crate::RenderObserver`].
+#[deprecated(
+    since = "0.4.0",
+    note = "use `list_archive_frames` and `extract_archive_frames`"
+)]
+pub fn unzip_frames(
+    zip_path: &Path,
+    subfolder: Option<&str>,
+    _verbose: bool,
+) -> Result<(PathBuf, tempfile::TempDir), RenderError> {
+    let formats = [ImageFormat::Png];
+    let entries = list_archive_frames(zip_path, "*", subfolder, &formats)?;
+    if entries.is_empty() {
+        return Err(RenderError::NoFramesMatched {
+            dir: subfolder
+                .map(|sub| zip_path.join(sub))
+                .unwrap_or_else(|| zip_path.to_path_buf()),
+            pattern: ImageFormat::default_pattern(&formats),
+        });
     }
-    Ok(count)
+    let temp_dir =
+        tempfile::tempdir().map_err(|e| RenderError::io("Failed to create temp dir", e))?;
+    extract_archive_frames(
+        zip_path,
+        &entries,
+        temp_dir.path(),
+        &CancellationToken::new(),
+    )?;
+    Ok((temp_dir.path().to_path_buf(), temp_dir))
+}
+
+/// Count PNG files inside a ZIP or tar archive (or inside `subfolder` of it)
+#[deprecated(since = "0.4.0", note = "use `list_archive_frames`")]
+pub fn count_pngs_in_zip(zip_path: &Path, subfolder: Option<&str>) -> Result<usize, RenderError> {
+    Ok(list_archive_frames(zip_path, "*", subfolder, &[ImageFormat::Png])?.len())
 }
 
-/// Extract a specific PNG frame from a ZIP archive
+/// List the image entries of a ZIP or tar archive matching `pattern`
+#[deprecated(since = "0.4.0", note = "renamed to `list_archive_frames`")]
+pub fn list_zip_frames(
+    zip_path: &Path,
+    pattern: &str,
+    subfolder: Option<&str>,
+    formats: &[ImageFormat],
+) -> Result<Vec<(usize, String)>, RenderError> {
+    list_archive_frames(zip_path, pattern, subfolder, formats)
+}
+
+/// Extract a specific frame of one of `formats` from a ZIP or tar archive
+#[deprecated(since = "0.4.0", note = "renamed to `extract_archive_frame`")]
 pub fn extract_frame_from_zip(
     zip_path: &Path,
+    subfolder: Option<&str>,
+    formats: &[ImageFormat],
     frame_index: usize,
     output: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let file = File::open(zip_path)
-        .map_err(|e| format!("❌ Failed to open zip file '{}': {}", zip_path.display(), e))?;
-    let mut archive =
-        ZipArchive::new(file).map_err(|e| format!("❌ Failed to read zip archive: {}", e))?;
-    let mut png_indices = Vec::new();
-    for i in 0..archive.len() {
-        let f = archive.by_index(i)?;
-        let name = f.name().rsplit('/').next().unwrap_or("");
-        if is_valid_image(name) {
-            png_indices.push(i);
-        }
-    }
-    if png_indices.is_empty() {
-        return Err("❌ No PNG files found in zip archive".into());
-    }
-    if frame_index >= png_indices.len() {
-        return Err(format!(
-            "❌ Frame index {} out of range (0..{})",
-            frame_index,
-            png_indices.len() - 1
-        )
-        .into());
-    }
-    let mut file = archive.by_index(png_indices[frame_index])?;
-    let mut out = File::create(output).map_err(|e| {
-        format!(
-            "❌ Failed to create output file '{}': {}",
-            output.display(),
-            e
-        )
-    })?;
-    std::io::copy(&mut file, &mut out)
-        .map_err(|e| format!("❌ Failed to copy content to '{}': {}", output.display(), e))?;
-    Ok(())
+) -> Result<(), RenderError> {
+    extract_archive_frame(zip_path, subfolder, formats, frame_index, output)
 }
 
 /// Open the rendered output in the default system viewer
@@ -207,29 +458,144 @@ pub fn scan_ffmpeg_stderr(stderr: &str) -> Vec<String> {
     warnings
 }
 
-pub fn run_ffmpeg_with_output(args: &[String]) -> Result<(ExitStatus, String), String> {
-    let output = Command::new("ffmpeg").args(args).output().map_err(|e| {
-        if e.kind() == std::io::ErrorKind::NotFound {
-            "❌ ffmpeg not found in PATH.".to_string()
+/// Writes input data to ffmpeg's stdin from a background thread
+pub type StdinFeed = Box<dyn FnOnce(&mut dyn Write) -> std::io::Result<()> + Send>;
+
+/// Run ffmpeg with `-progress pipe:1`, reporting each progress update to
+/// `on_progress` and returning the collected stderr once it exits.
+///
+/// The child process is killed and [`RenderError::Cancelled`] returned as soon
+/// as `cancel` is triggered.
+pub fn run_ffmpeg_with_output(
+    args: &[String],
+    on_progress: &mut dyn FnMut(&FfmpegProgress),
+    cancel: &CancellationToken,
+) -> Result<(ExitStatus, String), RenderError> {
+    run_ffmpeg_with_input(args, None, on_progress, cancel)
+}
+
+/// Like [`run_ffmpeg_with_output`], additionally streaming `stdin_feed` into
+/// ffmpeg's stdin for piped inputs such as `-i pipe:0`.
+pub fn run_ffmpeg_with_input(
+    args: &[String],
+    stdin_feed: Option<StdinFeed>,
+    on_progress: &mut dyn FnMut(&FfmpegProgress),
+    cancel: &CancellationToken,
+) -> Result<(ExitStatus, String), RenderError> {
+    cancel.check()?;
+    let mut child = Command::new("ffmpeg")
+        .args(["-progress", "pipe:1", "-nostats"])
+        .args(args)
+        .stdin(if stdin_feed.is_some() {
+            Stdio::piped()
         } else {
-            format!("❌ ffmpeg failed to run: {}", e)
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                RenderError::FfmpegNotFound
+            } else {
+                RenderError::io("Failed to execute ffmpeg", e)
+            }
+        })?;
+
+    // Stdin is closed when the writer finishes, signalling end of input
+    let stdin_writer = match (stdin_feed, child.stdin.take()) {
+        (Some(feed), Some(mut stdin)) => Some(thread::spawn(move || feed(&mut stdin))),
+        _ => None,
+    };
+
+    // Drain stderr on its own thread so a chatty ffmpeg can't block on a full pipe
+    let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let stderr_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr_pipe.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).into_owned()
+    });
+
+    // Parse progress on another thread so cancellation can be polled meanwhile
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let (tx, rx) = mpsc::channel();
+    let progress_reader = thread::spawn(move || {
+        let mut parser = ProgressParser::new();
+        for line in BufReader::new(stdout).lines() {
+            let Ok(line) = line else { break };
+            if let Some(update) = parser.feed(&line) {
+                if tx.send(update).is_err() {
+                    break;
+                }
+            }
         }
-    })?;
+    });
+
+    let mut cancelled = false;
+    loop {
+        if cancel.is_cancelled() {
+            let _ = child.kill();
+            cancelled = true;
+            break;
+        }
+        match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(update) => on_progress(&update),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
 
-    if !output.status.success() {
-        return Err(format!(
-            "❌ ffmpeg exited with code {}",
-            output.status.code().unwrap_or(-1)
-        ));
+    let status = child
+        .wait()
+        .map_err(|e| RenderError::io("Failed to wait for ffmpeg", e))?;
+    let _ = progress_reader.join();
+    let stderr = stderr_reader.join().unwrap_or_default();
+    let feed_result = stdin_writer.map(|writer| writer.join().unwrap_or(Ok(())));
+    if cancelled {
+        return Err(RenderError::Cancelled);
+    }
+    // ffmpeg may stop reading early (e.g. a frame limit); only other errors
+    // matter, and they explain an ffmpeg failure better than its stderr
+    if let Some(Err(e)) = feed_result {
+        if e.kind() != std::io::ErrorKind::BrokenPipe {
+            return Err(feed_error(e));
+        }
     }
+    if !status.success() {
+        return Err(RenderError::FfmpegFailed {
+            exit_code: status.code(),
+            stderr,
+        });
+    }
+
+    Ok((status, stderr))
+}
 
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    Ok((output.status, stderr))
+/// The error a stdin feed failed with, unwrapping frames it rejected
+fn feed_error(e: std::io::Error) -> RenderError {
+    if !e.get_ref().is_some_and(|inner| inner.is::<RenderError>()) {
+        return RenderError::io("Failed to stream frames to ffmpeg", e);
+    }
+    match e.into_inner().map(|inner| inner.downcast::<RenderError>()) {
+        Some(Ok(err)) => *err,
+        _ => unreachable!("feed error holds a RenderError"),
+    }
+}
+
+/// Remove a half-written output file left behind by a cancelled ffmpeg run
+pub(crate) fn remove_partial_output(err: &RenderError, output: &str) {
+    if matches!(err, RenderError::Cancelled) {
+        let _ = std::fs::remove_file(output);
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::unzip_frames;
+    use super::{extract_archive_frame, extract_archive_frames, list_archive_frames, ArchiveKind};
+    use crate::cancel::CancellationToken;
+    use crate::error::RenderError;
+    use crate::ffmpeg::source::FrameSource;
+    use crate::input::ImageFormat;
     use std::fs::File;
     use std::io::Write;
     use std::path::Path;
@@ -252,18 +618,229 @@ mod tests {
     }
 
     #[test]
-    fn unzip_frames_extracts_pngs() -> Result<(), Box<dyn std::error::Error>> {
+    fn extract_archive_frames_writes_pngs() -> Result<(), Box<dyn std::error::Error>> {
         let dir = tempdir()?;
         let zip_path = dir.path().join("frames.zip");
         create_test_zip(&zip_path)?;
 
-        let (out_dir, _guard) = unzip_frames(&zip_path, false)?;
+        let frames = list_archive_frames(&zip_path, "*.png", None, &[ImageFormat::Png])?;
+        let out_dir = tempdir()?;
+        let cancel = CancellationToken::new();
+        extract_archive_frames(&zip_path, &frames, out_dir.path(), &cancel)?;
 
-        let count = std::fs::read_dir(&out_dir)?.count();
+        let count = std::fs::read_dir(out_dir.path())?.count();
         assert_eq!(count, 2);
-        assert!(out_dir.join("frame_0000.png").exists());
-        assert!(out_dir.join("frame_0001.png").exists());
+        assert!(out_dir.path().join("frame_0000.png").exists());
+        assert!(out_dir.path().join("frame_0001.png").exists());
+
+        // A cancelled token stops extraction before anything is written
+        let cancelled = CancellationToken::new();
+        cancelled.cancel();
+        let empty = tempdir()?;
+        let err = extract_archive_frames(&zip_path, &frames, empty.path(), &cancelled);
+        assert!(matches!(err, Err(RenderError::Cancelled)));
+        assert_eq!(std::fs::read_dir(empty.path())?.count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn zip_helpers_keep_working_under_their_old_names() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("frames.zip");
+        create_test_zip(&zip_path)?;
+
+        assert_eq!(super::count_pngs_in_zip(&zip_path, None)?, 2);
+        let (frames_dir, _guard) = super::unzip_frames(&zip_path, None, false)?;
+        assert!(frames_dir.join("frame_0001.png").exists());
+        let output = dir.path().join("first.png");
+        super::extract_frame_from_zip(&zip_path, None, &[ImageFormat::Png], 0, &output)?;
+        assert_eq!(std::fs::read(&output)?, b"png0");
+        Ok(())
+    }
+
+    #[test]
+    fn rejected_frames_surface_from_the_feed() {
+        let rejected = std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            RenderError::CorruptFrames(vec!["RGBA frame 2 has 3 bytes".into()]),
+        );
+        assert!(matches!(
+            super::feed_error(rejected),
+            RenderError::CorruptFrames(frames) if frames.len() == 1
+        ));
+        let other = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(matches!(super::feed_error(other), RenderError::Io { .. }));
+    }
+
+    #[test]
+    fn damaged_archives_are_read_by_extension() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let broken = dir.path().join("frames.zip");
+        std::fs::write(&broken, b"truncated download")?;
+        assert_eq!(ArchiveKind::detect(&broken), None);
+        assert_eq!(ArchiveKind::of_path(&broken), Some(ArchiveKind::Zip));
+        assert!(matches!(
+            list_archive_frames(&broken, "*.png", None, &[ImageFormat::Png]),
+            Err(RenderError::ZipRead(_))
+        ));
+
+        let other = dir.path().join("frames.bin");
+        std::fs::write(&other, b"???")?;
+        assert_eq!(ArchiveKind::of_path(&other), None);
+        assert!(matches!(
+            list_archive_frames(&other, "*.png", None, &[ImageFormat::Png]),
+            Err(RenderError::UnrecognizedInput(_))
+        ));
+        assert_eq!(
+            ArchiveKind::from_extension(Path::new("shots/Render.TAR.ZST")),
+            Some(ArchiveKind::TarZst)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn zip_frames_stream_in_name_order() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("frames.zip");
+        let mut zip = ZipWriter::new(File::create(&zip_path)?);
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+        for (name, data) in [
+            ("frame_0001.png", b"png1"),
+            ("__MACOSX/._frame_0001.png", b"meta"),
+            ("frame_0000.png", b"png0"),
+            ("notes.txt", b"text"),
+        ] {
+            zip.start_file(name, options)?;
+            zip.write_all(data)?;
+        }
+        zip.finish()?;
+
+        let frames = list_archive_frames(&zip_path, "*.png", None, &[ImageFormat::Png])?;
+        let names: Vec<_> = frames.iter().map(|(_, name)| name.as_str()).collect();
+        assert_eq!(names, ["frame_0000.png", "frame_0001.png"]);
+
+        let source = FrameSource::ZipStream {
+            archive: zip_path,
+            entries: frames.into_iter().map(|(index, _)| index).collect(),
+            format: ImageFormat::Png,
+        };
+        let mut piped = Vec::new();
+        (source.stdin_feed().unwrap())(&mut piped)?;
+        assert_eq!(piped, b"png0png1");
+
+        Ok(())
+    }
+
+    fn create_nested_zip(path: &Path) -> zip::result::ZipResult<()> {
+        let mut zip = ZipWriter::new(File::create(path)?);
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+        for (name, data) in [
+            ("shotB/0000.png", b"b0"),
+            ("shotA/0001.png", b"a1"),
+            ("shotA/0000.png", b"a0"),
+        ] {
+            zip.start_file(name, options)?;
+            zip.write_all(data)?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    #[test]
+    fn zip_subfolder_selects_frames_in_path_order() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("shots.zip");
+        create_nested_zip(&zip_path)?;
 
+        let frames = list_archive_frames(&zip_path, "*.png", Some("shotA/"), &[ImageFormat::Png])?;
+        let names: Vec<_> = frames.iter().map(|(_, name)| name.as_str()).collect();
+        assert_eq!(names, ["shotA/0000.png", "shotA/0001.png"]);
+
+        let preview = dir.path().join("preview.png");
+        extract_archive_frame(&zip_path, Some("shotA"), &[ImageFormat::Png], 1, &preview)?;
+        assert_eq!(std::fs::read(&preview)?, b"a1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn zip_basename_collisions_are_rejected() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let zip_path = dir.path().join("shots.zip");
+        create_nested_zip(&zip_path)?;
+
+        let err = list_archive_frames(&zip_path, "*.png", None, &[ImageFormat::Png]).unwrap_err();
+        match err {
+            RenderError::DuplicateFrameName {
+                name,
+                first,
+                second,
+            } => {
+                assert_eq!(name, "0000.png");
+                assert_eq!(first, "shotA/0000.png");
+                assert_eq!(second, "shotB/0000.png");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+
+        Ok(())
+    }
+
+    fn write_tar<W: Write>(out: W) -> std::io::Result<W> {
+        let mut tar = tar::Builder::new(out);
+        for (name, data) in [
+            ("render/frame_10.png", b"p10"),
+            ("render/frame_9.png", b"p09"),
+            ("render/notes.txt", b"txt"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, &data[..])?;
+        }
+        tar.into_inner()
+    }
+
+    #[test]
+    fn tar_family_is_detected_by_content() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        // Misleading extensions on purpose: detection must not rely on them
+        let plain = dir.path().join("frames.bin");
+        write_tar(File::create(&plain)?)?;
+        let gz = dir.path().join("frames.zip");
+        write_tar(flate2::write::GzEncoder::new(
+            File::create(&gz)?,
+            flate2::Compression::fast(),
+        ))?
+        .finish()?;
+        let zst = dir.path().join("frames.tar");
+        write_tar(zstd::stream::write::Encoder::new(File::create(&zst)?, 0)?)?.finish()?;
+
+        for (path, kind) in [
+            (&plain, ArchiveKind::Tar),
+            (&gz, ArchiveKind::TarGz),
+            (&zst, ArchiveKind::TarZst),
+        ] {
+            assert_eq!(ArchiveKind::detect(path), Some(kind));
+            let frames = list_archive_frames(path, "*.png", None, &[ImageFormat::Png])?;
+            let names: Vec<_> = frames.iter().map(|(_, name)| name.as_str()).collect();
+            assert_eq!(names, ["render/frame_9.png", "render/frame_10.png"]);
+            let nested = list_archive_frames(path, "*", Some("render"), &[ImageFormat::Png])?;
+            assert_eq!(nested.len(), 2);
+
+            let out = tempdir()?;
+            let cancel = CancellationToken::new();
+            let paths = extract_archive_frames(path, &frames, out.path(), &cancel)?;
+            assert_eq!(std::fs::read(&paths[1])?, b"p10");
+
+            let preview = out.path().join("preview.png");
+            extract_archive_frame(path, None, &[ImageFormat::Png], 0, &preview)?;
+            assert_eq!(std::fs::read(&preview)?, b"p09");
+        }
+        assert_eq!(ArchiveKind::detect(dir.path()), None);
         Ok(())
     }
 }
@@ -0,0 +1,505 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Seek};
+
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use zip::ZipArchive;
+
+use crate::cancel::CancellationToken;
+use crate::error::RenderError;
+use crate::ffmpeg::source::FrameSource;
+use crate::input::ImageFormat;
+
+/// What the pre-flight check learned about the input frames
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameCheck {
+    pub width: u32,
+    pub height: u32,
+    /// Pixel layout of the first frame, e.g. `Rgba8` or `Rgb16`
+    pub color_type: String,
+    /// Whether any frame has an alpha channel or, when frames were fully
+    /// decoded, a pixel that is not fully opaque
+    pub uses_alpha: bool,
+    /// Number of distinct frames checked
+    pub frames_checked: usize,
+    /// Whether every frame was fully decoded rather than only its header read
+    pub decoded: bool,
+    /// Inconsistencies ffmpeg copes with, such as RGB frames mixed with RGBA
+    pub warnings: Vec<String>,
+}
+
+impl fmt::Display for FrameCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{} {}, {}",
+            self.width,
+            self.height,
+            self.color_type,
+            match (self.uses_alpha, self.decoded) {
+                (true, true) => "uses alpha",
+                (false, true) => "fully opaque",
+                (true, false) => "has an alpha channel",
+                (false, false) => "no alpha channel",
+            }
+        )
+    }
+}
+
+/// Check every frame of `source` before encoding, so unreadable or
+/// mismatched files are reported by name instead of as an ffmpeg failure.
+///
+/// Only image headers are read by default, which is cheap even for long
+/// sequences. With `decode`, every frame is fully decoded as well, catching
+/// truncated image data and telling whether alpha is actually used.
+///
+/// In-memory PNG frames only have their first frame checked, since streamed
+/// frames can't be read twice; buffered RGBA frames have their sizes checked.
+///
+/// Returns `None` for sources that cannot be inspected up front (patterns,
+/// raw RGBA frames and videos).
+pub fn validate_source(
+    source: &FrameSource,
+    decode: bool,
+    cancel: &CancellationToken,
+) -> Result<Option<FrameCheck>, RenderError> {
+    if let FrameSource::Memory(frames) = source {
+        frames.check_buffered()?;
+    }
+    let Some(format) = source.format() else {
+        return Ok(None);
+    };
+    let mut validator = Validator::new(format, decode);
+    match source {
+        FrameSource::Files(files) => {
+            let mut seen = HashSet::new();
+            for path in files.iter().filter(|p| seen.insert(*p)) {
+                cancel.check()?;
+                let name = path.display().to_string();
+                match File::open(path) {
+                    Ok(file) => validator.check(name, BufReader::new(file)),
+                    Err(e) => validator.corrupt.push(format!("{}: {}", name, e)),
+                }
+            }
+        }
+        FrameSource::ZipStream {
+            archive, entries, ..
+        } => {
+            let file = File::open(archive)
+                .map_err(|e| RenderError::io("Failed to open frame archive", e))?;
+            let mut zip = ZipArchive::new(file)?;
+            let mut seen = HashSet::new();
+            for &index in entries.iter().filter(|i| seen.insert(**i)) {
+                cancel.check()?;
+                let mut entry = zip.by_index(index)?;
+                let name = entry.name().to_string();
+                // Headers sit at the start of the file, so skip inflating the rest
+                let limit = if decode { u64::MAX } else { HEADER_BYTES };
+                let mut bytes = Vec::new();
+                if let Err(e) = entry.by_ref().take(limit).read_to_end(&mut bytes) {
+                    validator.corrupt.push(format!("{}: {}", name, e));
+                    continue;
+                }
+                let mut header = validator.read(Cursor::new(&bytes));
+                // Some headers end past the prefix (TIFF often writes its
+                // directory last), so retry those with the whole entry
+                if header.is_err() && bytes.len() as u64 == limit {
+                    if let Err(e) = entry.read_to_end(&mut bytes) {
+                        validator.corrupt.push(format!("{}: {}", name, e));
+                        continue;
+                    }
+                    header = validator.read(Cursor::new(&bytes));
+                }
+                validator.record(name, header);
+            }
+        }
+        FrameSource::Memory(frames) => {
+            // Only the first streamed frame can be read without consuming it
+            if let Some(first) = frames.first() {
+                validator.check("frame 0".into(), Cursor::new(first));
+            }
+        }
+        FrameSource::Pattern(_) | FrameSource::Video { .. } => return Ok(None),
+    }
+    validator.finish()
+}
+
+/// Bytes of a ZIP entry inflated to read its header when frames are not
+/// fully decoded
+const HEADER_BYTES: u64 = 64 * 1024;
+
+/// Running state of a validation pass
+struct Validator {
+    format: image::ImageFormat,
+    decode: bool,
+    /// Name, dimensions and color type of the first frame checked
+    first: Option<(String, u32, u32, image::ColorType)>,
+    mismatch: Option<RenderError>,
+    corrupt: Vec<String>,
+    warnings: Vec<String>,
+    uses_alpha: bool,
+    checked: usize,
+}
+
+impl Validator {
+    fn new(format: ImageFormat, decode: bool) -> Self {
+        Self {
+            format: decoder_format(format),
+            decode,
+            first: None,
+            mismatch: None,
+            corrupt: Vec::new(),
+            warnings: Vec::new(),
+            uses_alpha: false,
+            checked: 0,
+        }
+    }
+
+    fn check(&mut self, name: String, reader: impl BufRead + Seek) {
+        let header = self.read(reader);
+        self.record(name, header);
+    }
+
+    /// Read the dimensions and color type of one frame, decoding it fully
+    /// when asked to
+    fn read(
+        &mut self,
+        reader: impl BufRead + Seek,
+    ) -> image::ImageResult<(u32, u32, image::ColorType)> {
+        let reader = ImageReader::with_format(reader, self.format);
+        if self.decode {
+            reader.decode().map(|image| {
+                // One translucent pixel is enough; later frames only need decoding
+                self.uses_alpha = self.uses_alpha || uses_alpha(&image);
+                (image.width(), image.height(), image.color())
+            })
+        } else {
+            reader.into_decoder().map(|decoder| {
+                let (width, height) = decoder.dimensions();
+                let color = decoder.color_type();
+                self.uses_alpha = self.uses_alpha || color.has_alpha();
+                (width, height, color)
+            })
+        }
+    }
+
+    fn record(&mut self, name: String, header: image::ImageResult<(u32, u32, image::ColorType)>) {
+        self.checked += 1;
+        let (width, height, color) = match header {
+            Ok(header) => header,
+            Err(e) => {
+                self.corrupt.push(format!("{}: {}", name, e));
+                return;
+            }
+        };
+        let Some((first, w, h, c)) = &self.first else {
+            self.first = Some((name, width, height, color));
+            return;
+        };
+        if (width, height, color) == (*w, *h, *c) {
+            return;
+        }
+        if (width, height, without_alpha(color)) == (*w, *h, without_alpha(*c)) {
+            // Optimised PNG sequences drop alpha from fully opaque frames
+            if self.warnings.is_empty() {
+                self.warnings.push(format!(
+                    "Frames mix color types with and without alpha ('{}' is {:?}, '{}' is {:?})",
+                    first, c, name, color
+                ));
+            }
+        } else if self.mismatch.is_none() {
+            self.mismatch = Some(RenderError::FrameMismatch {
+                first: first.clone(),
+                expected: describe(*w, *h, *c),
+                other: name,
+                found: describe(width, height, color),
+            });
+        }
+    }
+
+    fn finish(self) -> Result<Option<FrameCheck>, RenderError> {
+        if !self.corrupt.is_empty() {
+            return Err(RenderError::CorruptFrames(self.corrupt));
+        }
+        if let Some(mismatch) = self.mismatch {
+            return Err(mismatch);
+        }
+        Ok(self.first.map(|(_, width, height, color)| FrameCheck {
+            width,
+            height,
+            color_type: format!("{:?}", color),
+            uses_alpha: self.uses_alpha,
+            frames_checked: self.checked,
+            decoded: self.decode,
+            warnings: self.warnings,
+        }))
+    }
+}
+
+/// The same layout without its alpha channel
+fn without_alpha(color: image::ColorType) -> image::ColorType {
+    use image::ColorType::*;
+    match color {
+        La8 => L8,
+        Rgba8 => Rgb8,
+        La16 => L16,
+        Rgba16 => Rgb16,
+        Rgba32F => Rgb32F,
+        other => other,
+    }
+}
+
+fn describe(width: u32, height: u32, color: image::ColorType) -> String {
+    format!("{}x{} {:?}", width, height, color)
+}
+
+fn decoder_format(format: ImageFormat) -> image::ImageFormat {
+    match format {
+        ImageFormat::Png => image::ImageFormat::Png,
+        ImageFormat::Jpeg => image::ImageFormat::Jpeg,
+        ImageFormat::WebP => image::ImageFormat::WebP,
+        ImageFormat::Tiff => image::ImageFormat::Tiff,
+        ImageFormat::Exr => image::ImageFormat::OpenExr,
+        ImageFormat::Tga => image::ImageFormat::Tga,
+    }
+}
+
+/// Whether the image has an alpha channel and actually uses it
+fn uses_alpha(image: &DynamicImage) -> bool {
+    if !image.color().has_alpha() {
+        return false;
+    }
+    match image {
+        DynamicImage::ImageLumaA8(img) => img.pixels().any(|p| p[1] < u8::MAX),
+        DynamicImage::ImageRgba8(img) => img.pixels().any(|p| p[3] < u8::MAX),
+        DynamicImage::ImageLumaA16(img) => img.pixels().any(|p| p[1] < u16::MAX),
+        DynamicImage::ImageRgba16(img) => img.pixels().any(|p| p[3] < u16::MAX),
+        DynamicImage::ImageRgba32F(img) => img.pixels().any(|p| p[3] < 1.0),
+        other => other.to_rgba8().pixels().any(|p| p[3] < u8::MAX),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    fn write_png(path: &std::path::Path, width: u32, height: u32, alpha: u8) {
+        let image = image::RgbaImage::from_pixel(width, height, image::Rgba([10, 20, 30, alpha]));
+        image.save(path).unwrap();
+    }
+
+    fn validate(files: Vec<PathBuf>) -> Result<Option<FrameCheck>, RenderError> {
+        validate_source(&FrameSource::Files(files), false, &CancellationToken::new())
+    }
+
+    fn decode(files: Vec<PathBuf>) -> Result<Option<FrameCheck>, RenderError> {
+        validate_source(&FrameSource::Files(files), true, &CancellationToken::new())
+    }
+
+    #[test]
+    fn reports_size_and_alpha_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.png"), dir.path().join("b.png"));
+        write_png(&a, 4, 2, 255);
+        write_png(&b, 4, 2, 128);
+
+        // Headers only tell that an alpha channel exists
+        let check = validate(vec![a.clone(), a.clone()]).unwrap().unwrap();
+        assert_eq!((check.width, check.height), (4, 2));
+        assert_eq!(check.color_type, "Rgba8");
+        assert!(check.uses_alpha);
+        assert!(!check.decoded);
+        assert_eq!(check.frames_checked, 1);
+
+        let check = decode(vec![a.clone(), a.clone()]).unwrap().unwrap();
+        assert!(!check.uses_alpha);
+        assert_eq!(check.to_string(), "4x2 Rgba8, fully opaque");
+
+        let check = decode(vec![a, b]).unwrap().unwrap();
+        assert!(check.uses_alpha);
+        assert_eq!(check.frames_checked, 2);
+    }
+
+    #[test]
+    fn names_every_corrupt_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let good = dir.path().join("good.png");
+        write_png(&good, 4, 4, 255);
+        let bytes = std::fs::read(&good).unwrap();
+        let truncated = dir.path().join("truncated.png");
+        std::fs::write(&truncated, &bytes[..bytes.len() / 2]).unwrap();
+        let garbage = dir.path().join("garbage.png");
+        std::fs::write(&garbage, b"not a png").unwrap();
+
+        match decode(vec![good.clone(), truncated.clone(), garbage.clone()]) {
+            Err(RenderError::CorruptFrames(frames)) => {
+                assert_eq!(frames.len(), 2);
+                assert!(frames[0].contains("truncated.png"));
+                assert!(frames[1].contains("garbage.png"));
+            }
+            other => panic!("expected CorruptFrames, got {:?}", other),
+        }
+
+        // A truncated body keeps a valid header; only unreadable headers fail
+        match validate(vec![good, truncated, garbage]) {
+            Err(RenderError::CorruptFrames(frames)) => {
+                assert_eq!(frames.len(), 1);
+                assert!(frames[0].contains("garbage.png"));
+            }
+            other => panic!("expected CorruptFrames, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn rejects_mismatched_dimensions_and_color_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b, c) = (
+            dir.path().join("a.png"),
+            dir.path().join("b.png"),
+            dir.path().join("c.png"),
+        );
+        write_png(&a, 4, 4, 255);
+        write_png(&b, 8, 4, 255);
+        image::ImageBuffer::<image::Rgb<u16>, _>::new(4, 4)
+            .save(&c)
+            .unwrap();
+
+        match validate(vec![a.clone(), b]) {
+            Err(RenderError::FrameMismatch {
+                expected, found, ..
+            }) => {
+                assert_eq!(expected, "4x4 Rgba8");
+                assert_eq!(found, "8x4 Rgba8");
+            }
+            other => panic!("expected FrameMismatch, got {:?}", other),
+        }
+        match validate(vec![a, c]) {
+            Err(RenderError::FrameMismatch { found, .. }) => assert_eq!(found, "4x4 Rgb16"),
+            other => panic!("expected FrameMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn mixed_rgb_and_rgba_frames_only_warn() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.png"), dir.path().join("b.png"));
+        image::RgbImage::new(4, 4).save(&a).unwrap();
+        write_png(&b, 4, 4, 128);
+
+        let check = validate(vec![a, b]).unwrap().unwrap();
+        assert_eq!(check.color_type, "Rgb8");
+        assert!(check.uses_alpha);
+        assert_eq!(check.warnings.len(), 1);
+        assert!(check.warnings[0].contains("is Rgba8"));
+    }
+
+    #[test]
+    fn checks_zip_entries_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("frames.zip");
+        let mut png = Vec::new();
+        image::RgbaImage::new(2, 2)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.start_file("shot/0001.png", options).unwrap();
+        std::io::Write::write_all(&mut zip, &png).unwrap();
+        zip.start_file("shot/0002.png", options).unwrap();
+        std::io::Write::write_all(&mut zip, b"broken").unwrap();
+        zip.finish().unwrap();
+
+        let source = FrameSource::ZipStream {
+            archive,
+            entries: vec![0, 1],
+            format: ImageFormat::Png,
+        };
+        match validate_source(&source, false, &CancellationToken::new()) {
+            Err(RenderError::CorruptFrames(frames)) => {
+                assert_eq!(frames.len(), 1);
+                assert!(frames[0].starts_with("shot/0002.png: "));
+            }
+            other => panic!("expected CorruptFrames, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn zip_headers_are_read_from_a_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("frames.zip");
+        // Noise keeps the PNG well above the header prefix
+        let noise = image::RgbaImage::from_fn(256, 256, |x, y| {
+            let v = (x * 7919 + y * 104_729) as u8 ^ (x * y) as u8;
+            image::Rgba([v, v.wrapping_mul(31), v.wrapping_add(97), 255])
+        });
+        let mut png = Vec::new();
+        noise
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        assert!(png.len() as u64 > 2 * HEADER_BYTES);
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("0001.png", zip::write::FileOptions::default())
+            .unwrap();
+        std::io::Write::write_all(&mut zip, &png[..png.len() - HEADER_BYTES as usize]).unwrap();
+        zip.finish().unwrap();
+
+        let source = FrameSource::ZipStream {
+            archive,
+            entries: vec![0],
+            format: ImageFormat::Png,
+        };
+        // The truncated tail is never inflated without a full decode
+        let check = validate_source(&source, false, &CancellationToken::new())
+            .unwrap()
+            .unwrap();
+        assert_eq!((check.width, check.height), (256, 256));
+        assert!(matches!(
+            validate_source(&source, true, &CancellationToken::new()),
+            Err(RenderError::CorruptFrames(_))
+        ));
+    }
+
+    #[test]
+    fn checks_memory_frames_up_front() {
+        use crate::ffmpeg::source::MemoryFrames;
+        let cancel = CancellationToken::new();
+        let mut png = Vec::new();
+        image::RgbaImage::new(3, 2)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        // The first streamed frame is peeked at, not consumed
+        let frames = MemoryFrames::png(vec![png.clone(), png].into_iter().filter(|_| true));
+        let source = FrameSource::Memory(frames.clone());
+        let check = validate_source(&source, false, &cancel).unwrap().unwrap();
+        assert_eq!((check.width, check.height), (3, 2));
+        (source.stdin_feed().unwrap())(&mut Vec::new()).unwrap();
+        assert_eq!(frames.frames_fed(), 2);
+
+        let garbage = FrameSource::Memory(MemoryFrames::png(vec![b"nope".to_vec()]));
+        match validate_source(&garbage, false, &cancel) {
+            Err(RenderError::CorruptFrames(frames)) => assert!(frames[0].starts_with("frame 0: ")),
+            other => panic!("expected CorruptFrames, got {:?}", other),
+        }
+
+        let rgba = MemoryFrames::rgba(2, 2, vec![vec![0; 16], vec![0; 12]]).buffered();
+        match validate_source(&FrameSource::Memory(rgba), false, &cancel) {
+            Err(RenderError::CorruptFrames(frames)) => {
+                assert_eq!(frames, ["RGBA frame 1 has 12 bytes, expected 16 for 2x2"]);
+            }
+            other => panic!("expected CorruptFrames, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn skips_sources_that_cannot_be_inspected() {
+        let source = FrameSource::Pattern("frames/*.png".into());
+        assert_eq!(
+            validate_source(&source, false, &CancellationToken::new()).unwrap(),
+            None
+        );
+    }
+}
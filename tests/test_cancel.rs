@@ -0,0 +1,40 @@
+use aether_renderer_core::{
+    render_cancellable, CancellationToken, NoopObserver, RenderConfig, RenderError,
+};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[test]
+fn cancelled_render_returns_cancelled_and_keeps_existing_output(
+) -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = tempdir()?;
+    let output = tmp.path().join("out.webm");
+    fs::write(&output, b"previous render")?;
+
+    let cfg = RenderConfig {
+        input: PathBuf::from("tests/testdata"),
+        output: output.to_string_lossy().into_owned(),
+        fps: 30,
+        format: "webm".into(),
+        fade_in: 0.0,
+        fade_out: 0.0,
+        bitrate: None,
+        crf: None,
+        open: false,
+        preview: None,
+        file_pattern: Some("frame_*.png".into()),
+        verbose: false,
+        verbose_ffmpeg: false,
+        ..Default::default()
+    };
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let result = render_cancellable(cfg, &mut NoopObserver, &token);
+
+    assert!(matches!(result, Err(RenderError::Cancelled)));
+    // ffmpeg never started, so nothing was overwritten or removed
+    assert_eq!(fs::read(&output)?, b"previous render");
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use aether_renderer_core::{render, RenderConfig};
+use aether_renderer_core::ffmpeg::source::FrameSource;
+use aether_renderer_core::{render, RenderConfig, RenderError};
 use std::path::PathBuf;
 
 #[test]
@@ -17,12 +18,14 @@ fn test_nonexistent_input_path() {
         file_pattern: None,
         verbose: false,
         verbose_ffmpeg: false,
+        ..Default::default()
     };
 
     let result = render(cfg);
     assert!(result.is_err());
     let err = result.unwrap_err();
-    assert!(err.contains("does not exist"));
+    assert!(matches!(err, RenderError::InputNotFound(_)));
+    assert!(err.to_string().contains("does not exist"));
 }
 
 #[test]
@@ -41,13 +44,15 @@ fn test_unsupported_format() {
         file_pattern: None,
         verbose: false,
         verbose_ffmpeg: false,
+        ..Default::default()
     };
 
     let result = render(cfg);
     assert!(result.is_err());
     let err = result.unwrap_err();
     print!("Error: {}", err);
-    assert!(err.contains("Unsupported format"));
+    assert!(matches!(err, RenderError::UnsupportedFormat(ref f) if f == "avi"));
+    assert!(err.to_string().contains("Unsupported format"));
 }
 
 #[test]
@@ -66,13 +71,16 @@ fn test_unsupported_format2() {
         file_pattern: None,
         verbose: false,
         verbose_ffmpeg: false,
+        ..Default::default()
     };
 
     let result = render(cfg);
     assert!(result.is_err());
     let err = result.unwrap_err();
     print!("Error: {}", err);
-    assert!(err.contains("Input path 'frames/' does not exist"));
+    assert!(err
+        .to_string()
+        .contains("Input path 'frames/' does not exist"));
 }
 
 #[test]
@@ -91,6 +99,7 @@ fn test_unsupported_format3() {
         file_pattern: None,
         verbose: false,
         verbose_ffmpeg: false,
+        ..Default::default()
     };
 
     let result = render(cfg);
@@ -101,3 +110,73 @@ fn test_unsupported_format3() {
     let report = result.unwrap();
     assert!(report.notes.unwrap_or_default().contains("⚠️ Warning"));
 }
+
+#[test]
+fn test_webp_quality_out_of_range() {
+    let result = aether_renderer_core::ffmpeg::webp::render_webp(
+        &FrameSource::Pattern("frames/*.png".into()),
+        "out.webp",
+        30,
+        false,
+        Some(150),
+        None,
+        &aether_renderer_core::ffmpeg::filter::FilterGraph::new(),
+        false,
+        &mut aether_renderer_core::NoopObserver,
+        &aether_renderer_core::CancellationToken::new(),
+    );
+
+    assert!(matches!(
+        result,
+        Err(RenderError::InvalidOption {
+            name: "quality",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_corrupt_frame_is_named_before_encoding() -> Result<(), Box<dyn std::error::Error>> {
+    if std::process::Command::new("ffmpeg")
+        .arg("-version")
+        .output()
+        .is_err()
+    {
+        eprintln!("skipping test_corrupt_frame_is_named_before_encoding - ffmpeg not installed");
+        return Ok(());
+    }
+    let dir = tempfile::tempdir()?;
+    let frame = std::fs::read("tests/testdata/frame_0016.png")?;
+    std::fs::write(dir.path().join("frame_0001.png"), &frame)?;
+    std::fs::write(dir.path().join("frame_0002.png"), &frame[..frame.len() / 2])?;
+
+    let cfg = RenderConfig {
+        input: dir.path().to_path_buf(),
+        output: dir.path().join("out.webm").to_string_lossy().into(),
+        // A truncated body keeps a valid header, so it takes a full decode
+        decode_frames: true,
+        ..Default::default()
+    };
+    match render(cfg) {
+        Err(RenderError::CorruptFrames(frames)) => {
+            assert_eq!(frames.len(), 1);
+            assert!(frames[0].contains("frame_0002.png"));
+        }
+        other => panic!("expected CorruptFrames, got {:?}", other),
+    }
+    assert!(!dir.path().join("out.webm").exists());
+    Ok(())
+}
+
+#[test]
+fn test_corrupt_zip_reports_archive_error() {
+    // No ZIP magic bytes: the `.zip` extension still routes it to the ZIP reader
+    let cfg = RenderConfig {
+        input: PathBuf::from("tests/testdata/two-frames-error.zip"),
+        output: "tests/corrupt_preview.png".into(),
+        preview: Some(0),
+        ..Default::default()
+    };
+    let err = render(cfg).unwrap_err();
+    assert!(matches!(err, RenderError::ZipRead(_)), "got {:?}", err);
+}
@@ -0,0 +1,83 @@
+use aether_renderer_core::{render_frames, MemoryFrames, RenderConfig, RenderError};
+use std::process::Command;
+use tempfile::tempdir;
+
+fn ffmpeg_available() -> bool {
+    Command::new("ffmpeg").arg("-version").output().is_ok()
+}
+
+#[test]
+fn renders_png_bytes_without_files() -> Result<(), Box<dyn std::error::Error>> {
+    if !ffmpeg_available() {
+        eprintln!("skipping renders_png_bytes_without_files - ffmpeg not installed");
+        return Ok(());
+    }
+    let png = std::fs::read("tests/testdata/frame_0016.png")?;
+    let tmp = tempdir()?;
+    let output = tmp.path().join("memory.mp4");
+
+    // A filtered iterator has no exact length, so the fed count is reported
+    let frames = (0..6).map(move |_| png.clone()).filter(|_| true);
+    let cfg = RenderConfig {
+        output: output.to_string_lossy().into(),
+        format: "mp4".into(),
+        ..Default::default()
+    };
+    let report = render_frames(cfg, MemoryFrames::png(frames))?;
+    assert_eq!(report.frames_rendered, Some(6));
+    assert!(output.exists());
+    Ok(())
+}
+
+#[test]
+fn renders_raw_rgba_gif_in_two_passes() -> Result<(), Box<dyn std::error::Error>> {
+    if !ffmpeg_available() {
+        eprintln!("skipping renders_raw_rgba_gif_in_two_passes - ffmpeg not installed");
+        return Ok(());
+    }
+    let tmp = tempdir()?;
+    let output = tmp.path().join("memory.gif");
+    let frames = (0..4u8).map(|i| [i * 60, 0, 255 - i * 60, 255].repeat(16 * 16));
+    let cfg = RenderConfig {
+        output: output.to_string_lossy().into(),
+        format: "gif".into(),
+        fade_out: 0.05,
+        ..Default::default()
+    };
+    let report = render_frames(cfg, MemoryFrames::rgba(16, 16, frames))?;
+    assert_eq!(report.frames_rendered, Some(4));
+    assert!(output.exists());
+    Ok(())
+}
+
+#[test]
+fn empty_frame_stream_is_an_error() {
+    if !ffmpeg_available() {
+        return;
+    }
+    let cfg = RenderConfig {
+        output: "never.mp4".into(),
+        format: "mp4".into(),
+        ..Default::default()
+    };
+    let err = render_frames(cfg, MemoryFrames::png(Vec::new())).unwrap_err();
+    assert!(matches!(err, RenderError::NoMemoryFrames));
+}
+
+#[test]
+fn filtered_empty_frame_stream_is_an_error() {
+    if !ffmpeg_available() {
+        return;
+    }
+    let cfg = RenderConfig {
+        output: "never.mp4".into(),
+        format: "mp4".into(),
+        ..Default::default()
+    };
+    // No exact length up front, so the stream is peeked before ffmpeg starts
+    let frames = (0..3)
+        .map(|_| Vec::new())
+        .filter(|frame: &Vec<u8>| !frame.is_empty());
+    let err = render_frames(cfg, MemoryFrames::png(frames)).unwrap_err();
+    assert!(matches!(err, RenderError::NoMemoryFrames));
+}
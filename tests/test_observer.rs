@@ -0,0 +1,88 @@
+use aether_renderer_core::{
+    render_with_observer, RenderConfig, RenderError, RenderObserver, RenderReport, RenderStage,
+};
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[derive(Default)]
+struct Recorder {
+    stages: Vec<RenderStage>,
+    infos: Vec<(RenderStage, String)>,
+    warnings: Vec<String>,
+    finished: Option<bool>,
+}
+
+impl RenderObserver for Recorder {
+    fn on_stage(&mut self, stage: RenderStage) {
+        self.stages.push(stage);
+    }
+
+    fn on_info(&mut self, stage: RenderStage, message: &str) {
+        self.infos.push((stage, message.to_string()));
+    }
+
+    fn on_warning(&mut self, message: &str) {
+        self.warnings.push(message.to_string());
+    }
+
+    fn on_finished(&mut self, result: Result<&RenderReport, &RenderError>) {
+        self.finished = Some(result.is_ok());
+    }
+}
+
+fn config(input: PathBuf, output: String) -> RenderConfig {
+    RenderConfig {
+        input,
+        output,
+        fps: 30,
+        format: "webm".into(),
+        fade_in: 0.0,
+        fade_out: 0.0,
+        bitrate: None,
+        crf: None,
+        open: false,
+        preview: None,
+        file_pattern: None,
+        verbose: false,
+        verbose_ffmpeg: false,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn observer_sees_preview_stage_and_warning() -> Result<(), Box<dyn std::error::Error>> {
+    let tmp = tempdir()?;
+    let output = tmp.path().join("prev.webm");
+    let mut cfg = config(
+        PathBuf::from("tests/testdata/two-frames.zip"),
+        output.to_string_lossy().into_owned(),
+    );
+    cfg.preview = Some(0);
+    cfg.open = true;
+
+    let mut recorder = Recorder::default();
+    let report = render_with_observer(cfg, &mut recorder)?;
+
+    assert!(report.preview);
+    assert_eq!(recorder.stages, vec![RenderStage::ExtractingPreview]);
+    assert_eq!(recorder.warnings.len(), 1);
+    assert!(recorder.warnings[0].contains("--open"));
+    assert_eq!(recorder.infos.len(), 1);
+    assert_eq!(recorder.infos[0].0, RenderStage::ExtractingPreview);
+    assert!(recorder.infos[0].1.contains("Preview saved to"));
+    assert_eq!(recorder.finished, Some(true));
+    Ok(())
+}
+
+#[test]
+fn observer_is_notified_of_failure() {
+    let mut cfg = config(PathBuf::from("frames/"), "out.avi".into());
+    cfg.format = "avi".into();
+
+    let mut recorder = Recorder::default();
+    let result = render_with_observer(cfg, &mut recorder);
+
+    assert!(result.is_err());
+    assert!(recorder.stages.is_empty());
+    assert_eq!(recorder.finished, Some(false));
+}
@@ -1,6 +1,19 @@
 use aether_renderer_core::{render, RenderConfig, RenderReport};
 use std::fs;
 use std::path::PathBuf;
+use std::process::Command;
+
+/// Whether ffmpeg is installed and was built with `encoder`
+fn encoder_available(encoder: &str) -> bool {
+    Command::new("ffmpeg")
+        .args(["-hide_banner", "-encoders"])
+        .output()
+        .is_ok_and(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .split_whitespace()
+                .any(|word| word == encoder)
+        })
+}
 
 #[test]
 fn test_render_report_summary() {
@@ -44,6 +57,7 @@ fn test_render_video_from_folder() {
         file_pattern: Some("*.png".into()),
         verbose: false,
         verbose_ffmpeg: false,
+        ..Default::default()
     };
 
     let report = render(cfg).expect("Render should succeed");
@@ -73,12 +87,150 @@ fn test_render_gif_from_zip() {
         file_pattern: None,
         verbose: false,
         verbose_ffmpeg: false,
+        ..Default::default()
     };
 
     let report = render(cfg).expect("GIF render should succeed");
     assert!(PathBuf::from(output).exists());
     assert_eq!(report.frames_rendered, Some(2));
     assert!(report.ffmpeg_warnings.is_empty());
+    // The palette is generated in a temp dir, never in the working directory
+    assert!(!PathBuf::from("palette.png").exists());
+
+    fs::remove_file(output).ok();
+}
+
+#[test]
+fn test_render_webp_from_zip() {
+    if !encoder_available("libwebp_anim") {
+        eprintln!("skipping test_render_webp_from_zip - ffmpeg with libwebp_anim not installed");
+        return;
+    }
+    let input = PathBuf::from("tests/testdata/two-frames.zip");
+    let output = "tests/test.webp";
+
+    let cfg = RenderConfig {
+        input: input.clone(),
+        output: output.into(),
+        fps: 10,
+        format: "webp".into(),
+        lossless: true,
+        loop_count: Some(0),
+        ..Default::default()
+    };
+
+    let report = render(cfg).expect("WebP render should succeed");
+    assert!(PathBuf::from(output).exists());
+    assert_eq!(report.frames_rendered, Some(2));
+    assert!(report.notes.unwrap_or_default().contains("lossless"));
+
+    fs::remove_file(output).ok();
+}
+
+#[test]
+fn test_render_apng_from_zip() {
+    if !encoder_available("apng") {
+        eprintln!("skipping test_render_apng_from_zip - ffmpeg with apng not installed");
+        return;
+    }
+    let input = PathBuf::from("tests/testdata/two-frames.zip");
+    let output = "tests/test_anim.png";
+
+    let cfg = RenderConfig {
+        input: input.clone(),
+        output: output.into(),
+        fps: 10,
+        format: "apng".into(),
+        loop_count: Some(2),
+        max_colors: Some(128),
+        ..Default::default()
+    };
+
+    let report = render(cfg).expect("APNG render should succeed");
+    assert!(PathBuf::from(output).exists());
+    assert_eq!(report.frames_rendered, Some(2));
+    // `.png` is a valid APNG extension, so no mismatch warning is added
+    assert!(!report.notes.unwrap_or_default().contains("⚠️ Warning"));
 
     fs::remove_file(output).ok();
 }
+
+#[test]
+fn test_frame_gaps_are_held_and_reported() -> Result<(), Box<dyn std::error::Error>> {
+    if std::process::Command::new("ffmpeg")
+        .arg("-version")
+        .output()
+        .is_err()
+    {
+        eprintln!("skipping test_frame_gaps_are_held_and_reported - ffmpeg not installed");
+        return Ok(());
+    }
+    let tmp = tempfile::tempdir()?;
+    for name in ["frame_1.png", "frame_4.png"] {
+        fs::copy("tests/testdata/frame_0016.png", tmp.path().join(name))?;
+    }
+    let output = tmp.path().join("held.mp4");
+
+    let cfg = RenderConfig {
+        input: tmp.path().to_path_buf(),
+        output: output.to_string_lossy().into(),
+        format: "mp4".into(),
+        on_frame_gaps: Some("hold".into()),
+        ..Default::default()
+    };
+    let report = render(cfg)?;
+    assert_eq!(report.frames_rendered, Some(4));
+    assert_eq!(report.sequence_issues, ["frames 2–3 missing"]);
+    assert!(report.summary().contains("Frame sequence issues"));
+
+    let cfg = RenderConfig {
+        input: tmp.path().to_path_buf(),
+        output: output.to_string_lossy().into(),
+        format: "mp4".into(),
+        on_frame_gaps: Some("fail".into()),
+        ..Default::default()
+    };
+    let err = render(cfg).unwrap_err();
+    assert!(matches!(
+        err,
+        aether_renderer_core::RenderError::FrameSequenceGaps(_)
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_discarded_alpha_is_reported() -> Result<(), Box<dyn std::error::Error>> {
+    if std::process::Command::new("ffmpeg")
+        .arg("-version")
+        .output()
+        .is_err()
+    {
+        eprintln!("skipping test_discarded_alpha_is_reported - ffmpeg not installed");
+        return Ok(());
+    }
+    let tmp = tempfile::tempdir()?;
+    for (name, alpha) in [("frame_1.png", 255), ("frame_2.png", 96)] {
+        image::RgbaImage::from_pixel(8, 8, image::Rgba([200, 40, 40, alpha]))
+            .save(tmp.path().join(name))?;
+    }
+    let output = tmp.path().join("flat.mp4");
+    let config = |background: Option<&str>| RenderConfig {
+        input: tmp.path().to_path_buf(),
+        output: output.to_string_lossy().into(),
+        format: "mp4".into(),
+        background: background.map(Into::into),
+        ..Default::default()
+    };
+
+    let report = render(config(None))?;
+    let check = report.frame_check.as_ref().expect("frames are validated");
+    assert!(check.uses_alpha);
+    assert!(report
+        .notes
+        .unwrap_or_default()
+        .contains("transparent areas will turn black"));
+
+    let report = render(config(Some("#ffffff")))?;
+    assert!(!report.notes.unwrap_or_default().contains("⚠️ Warning"));
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use aether_renderer_core::utils::{run_ffmpeg_with_output, scan_ffmpeg_stderr};
+use aether_renderer_core::{CancellationToken, RenderError};
 
 #[test]
 fn test_scan_ffmpeg_stderr_detects_warnings() {
@@ -7,7 +8,7 @@ fn test_scan_ffmpeg_stderr_detects_warnings() {
 [Parsed_fps_0 @ 0x7fd660001c40] Frame rate very high for a muxer not efficiently supporting it.
 [Parsed_scale_0 @ 0x7fd660001b40] deprecated option 'flags' used
 \"";
-    let warnings = scan_ffmpeg_stderr(&fake_stderr);
+    let warnings = scan_ffmpeg_stderr(fake_stderr);
 
     assert!(warnings.iter().any(|w| w.contains("Past frame duration")));
     assert!(warnings.iter().any(|w| w.contains("Frame rate very high")));
@@ -16,9 +17,14 @@ fn test_scan_ffmpeg_stderr_detects_warnings() {
 
 #[test]
 fn test_run_ffmpeg_with_output_ffmpeg_not_found() {
-    let result = run_ffmpeg_with_output(&[String::from("-version-NOTREAL")]);
+    let result = run_ffmpeg_with_output(
+        &[String::from("-version-NOTREAL")],
+        &mut |_| {},
+        &CancellationToken::new(),
+    );
     assert!(result.is_err());
     let err = result.unwrap_err();
     println!("Error: {}", err);
-    assert!(err.contains("❌ ffmpeg exited with code"));
+    assert!(matches!(err, RenderError::FfmpegFailed { .. }));
+    assert!(err.to_string().contains("ffmpeg exited with code"));
 }
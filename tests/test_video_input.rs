@@ -0,0 +1,50 @@
+use aether_renderer_core::{render, RenderConfig};
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn tools_available() -> bool {
+    ["ffmpeg", "ffprobe"]
+        .iter()
+        .all(|tool| Command::new(tool).arg("-version").output().is_ok())
+}
+
+#[test]
+fn converts_webm_to_gif_and_previews_a_frame() -> Result<(), Box<dyn std::error::Error>> {
+    if !tools_available() {
+        eprintln!(
+            "skipping converts_webm_to_gif_and_previews_a_frame - ffmpeg/ffprobe not installed"
+        );
+        return Ok(());
+    }
+    let tmp = tempdir()?;
+    let webm = tmp.path().join("source.webm");
+    render(RenderConfig {
+        input: PathBuf::from("tests/testdata/two-frames.zip"),
+        output: webm.to_string_lossy().into(),
+        format: "webm-vp9".into(),
+        ..Default::default()
+    })?;
+
+    let gif = tmp.path().join("converted.gif");
+    let report = render(RenderConfig {
+        input: webm.clone(),
+        output: gif.to_string_lossy().into(),
+        format: "gif".into(),
+        fade_in: 0.01,
+        ..Default::default()
+    })?;
+    assert!(gif.exists());
+    assert!(report.frames_rendered.unwrap_or(0) > 0);
+
+    let report = render(RenderConfig {
+        input: webm,
+        output: tmp.path().join("still.gif").to_string_lossy().into(),
+        preview: Some(0),
+        ..Default::default()
+    })?;
+    assert!(report.preview);
+    assert_eq!(report.output_path, tmp.path().join("still.png"));
+    assert!(report.output_path.exists());
+    Ok(())
+}
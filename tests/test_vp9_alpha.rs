@@ -0,0 +1,86 @@
+use aether_renderer_core::{render, RenderConfig};
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn ffprobe(args: &[&str]) -> Option<String> {
+    let output = Command::new("ffprobe").args(args).output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[test]
+fn vp9_webm_keeps_alpha_plane() -> Result<(), Box<dyn std::error::Error>> {
+    if Command::new("ffmpeg").arg("-version").output().is_err()
+        || Command::new("ffprobe").arg("-version").output().is_err()
+    {
+        eprintln!("skipping vp9_webm_keeps_alpha_plane - ffmpeg/ffprobe not installed");
+        return Ok(());
+    }
+
+    let tmp = tempdir()?;
+    let output = tmp.path().join("alpha.webm");
+    let cfg = RenderConfig {
+        input: PathBuf::from("tests/testdata/two-frames.zip"),
+        output: output.to_string_lossy().into_owned(),
+        fps: 30,
+        format: "webm-vp9".into(),
+        fade_in: 0.0,
+        fade_out: 0.0,
+        bitrate: None,
+        crf: Some(30),
+        open: false,
+        preview: None,
+        file_pattern: None,
+        verbose: false,
+        verbose_ffmpeg: false,
+        ..Default::default()
+    };
+
+    let report = render(cfg)?;
+    assert_eq!(report.frames_rendered, Some(2));
+
+    let path = output.to_str().unwrap();
+    let codec = ffprobe(&[
+        "-v",
+        "error",
+        "-select_streams",
+        "v:0",
+        "-show_entries",
+        "stream=codec_name",
+        "-of",
+        "default=nw=1:nk=1",
+        path,
+    ]);
+    assert_eq!(codec.as_deref(), Some("vp9"));
+
+    // WebM stores the alpha plane as side data, flagged by the ALPHA_MODE tag
+    let alpha_mode = ffprobe(&[
+        "-v",
+        "error",
+        "-select_streams",
+        "v:0",
+        "-show_entries",
+        "stream_tags=alpha_mode",
+        "-of",
+        "default=nw=1:nk=1",
+        path,
+    ]);
+    assert_eq!(alpha_mode.as_deref(), Some("1"));
+
+    // Decoding with libvpx-vp9 must yield a pixel format with alpha
+    let pix_fmt = ffprobe(&[
+        "-v",
+        "error",
+        "-c:v",
+        "libvpx-vp9",
+        "-select_streams",
+        "v:0",
+        "-show_entries",
+        "stream=pix_fmt",
+        "-of",
+        "default=nw=1:nk=1",
+        path,
+    ]);
+    assert_eq!(pix_fmt.as_deref(), Some("yuva420p"));
+    Ok(())
+}